@@ -0,0 +1,783 @@
+use clap::parser::ValueSource;
+use clap::{ArgMatches, FromArgMatches};
+use rustix::fs::inotify;
+use serde::{Deserialize, Deserializer};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use time::macros::format_description;
+use time::Time;
+
+use crate::exercises::{Catalog, CatalogError, Category};
+use crate::logic::budget::BudgetSettings;
+use crate::logic::overtime::WorkingHours;
+use crate::logic::pomodoro::PomodoroSettings;
+use crate::logic::schedule::{Level, ScheduleKind, ScheduleSettings, ScheduleUrgency};
+use crate::logic::{
+    Mode, STATUS_CRITICAL, STATUS_ERROR, STATUS_OK, STATUS_OVERTIME, STATUS_OVER_BUDGET,
+    STATUS_PAUSED, STATUS_WARNING,
+};
+use crate::source::SourceKind;
+use crate::wayland::SeatPolicy;
+use crate::Args;
+
+/// Configuration file, every field is optional
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Idle timeout in seconds
+    pub idle_timeout: Option<u64>,
+    /// The maximum of active session before being notify
+    pub max_active_sessions: Option<u64>,
+    /// Waybar eye icon
+    pub icon: Option<String>,
+    /// Enable waybar module output
+    pub waybar: Option<bool>,
+    /// Eyes or pomodoro
+    pub mode: Option<Mode>,
+    /// Where the idle events come from
+    pub source: Option<SourceKind>,
+    /// Used instead when the idle source can't detect the idle time
+    pub fallback_source: Option<SourceKind>,
+    /// Combines the idle state of several seats
+    pub seat_policy: Option<SeatPolicy>,
+    /// Listen to systemd-logind
+    pub logind: Option<bool>,
+    /// Record the sessions history
+    pub history: Option<bool>,
+    /// Resume the session of the previous run
+    pub resume: Option<bool>,
+    /// Prometheus metrics file
+    pub metrics_file: Option<PathBuf>,
+    /// Notification settings
+    pub notification: NotificationConfig,
+    /// Waybar class names
+    pub classes: Option<Classes>,
+    /// Exercises suggested in the notifications
+    pub exercises: ExercisesConfig,
+    /// Micro and long break schedules
+    pub schedules: SchedulesConfig,
+    /// Pomodoro mode durations
+    pub pomodoro: PomodoroConfig,
+    /// Daily active time budget, disabled unless given
+    pub budget: Option<BudgetConfig>,
+    /// Working hours, the activity outside them is overtime, disabled unless given
+    pub working_hours: Option<WorkingHoursConfig>,
+    /// Long term burnout risk
+    pub risk: RiskConfig,
+}
+
+/// Notification settings
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// Send the notifications
+    pub enabled: Option<bool>,
+    /// Notification expiration in milliseconds, 0 never expires
+    pub timeout: Option<u32>,
+    /// Update the displayed notification or stack a new one
+    pub stacking: Option<Stacking>,
+}
+
+/// What to do with the displayed notification when a new one is sent
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stacking {
+    /// Update the displayed notification in place
+    #[default]
+    Replace,
+    /// Show a new popup every time
+    Stack,
+}
+
+/// Exercises settings
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExercisesConfig {
+    /// Keep the builtin exercises
+    pub builtin: Option<bool>,
+    /// User exercise catalog, default to ~/.config/burnout-detector/exercises.toml
+    pub catalog: Option<PathBuf>,
+    /// SafeEyes configuration whose breaks are added to the exercises
+    pub safeeyes: Option<PathBuf>,
+    /// Only suggest these categories, all of them when empty
+    pub categories: Vec<Category>,
+}
+
+/// Break schedules, disabled unless their table is given
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulesConfig {
+    /// Short and frequent breaks
+    pub micro: Option<ScheduleConfig>,
+    /// Long and rare breaks
+    pub long: Option<ScheduleConfig>,
+}
+
+/// Break schedule settings
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    /// Work time between two breaks, in seconds
+    pub interval: Option<u64>,
+    /// Time away needed for the break to count, in seconds
+    pub duration: Option<u64>,
+    /// Categories of the suggested exercises
+    pub categories: Option<Vec<Category>>,
+    /// Notification urgency when the break is due
+    pub urgency: Option<ScheduleUrgency>,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: Option<bool>,
+}
+
+/// Pomodoro durations, in seconds
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroConfig {
+    /// Work interval
+    pub work: Option<u64>,
+    /// Break after a work interval
+    pub short_break: Option<u64>,
+    /// Break after `long_break_after` work intervals
+    pub long_break: Option<u64>,
+    /// Work intervals before a long break, a count and not seconds
+    pub long_break_after: Option<u32>,
+    /// Added to a break cut short by activity, which otherwise ends it
+    pub extend: Option<u64>,
+}
+
+/// Daily active time budget
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// Active time allowed per day, in seconds
+    pub daily: Option<u64>,
+    /// Local time when a new day starts, `HH:MM`
+    #[serde(deserialize_with = "deserialize_time")]
+    pub day_start: Option<Time>,
+    /// Warn once this percentage of the budget is used
+    pub warning: Option<u32>,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: Option<bool>,
+}
+
+/// Parse a `HH:MM` time
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Time>, D::Error> {
+    let time = String::deserialize(deserializer)?;
+    Time::parse(&time, format_description!("[hour]:[minute]"))
+        .map(Some)
+        .map_err(|e| serde::de::Error::custom(format!("invalid time '{time}', {e}")))
+}
+
+/// Working hours of the weekdays, a day without hours is off
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkingHoursConfig {
+    /// Hours from monday to friday, unless given for the day
+    pub weekdays: Option<TimeRange>,
+    pub monday: Option<TimeRange>,
+    pub tuesday: Option<TimeRange>,
+    pub wednesday: Option<TimeRange>,
+    pub thursday: Option<TimeRange>,
+    pub friday: Option<TimeRange>,
+    pub saturday: Option<TimeRange>,
+    pub sunday: Option<TimeRange>,
+    /// Overtime before the first notification, in seconds
+    pub grace: Option<u64>,
+    /// Overtime between two notifications, in seconds
+    pub repeat: Option<u64>,
+    /// Time away ending the overtime, in seconds
+    pub rest: Option<u64>,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: Option<bool>,
+}
+
+/// Long term burnout risk computed from the history
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    /// Show the risk score in the waybar tooltip
+    pub tooltip: Option<bool>,
+    /// Send a summary notification once a week
+    pub weekly_summary: Option<bool>,
+}
+
+/// Local time range of a day, `HH:MM-HH:MM`, ending on the next day when it crosses midnight
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct TimeRange {
+    pub start: Time,
+    pub end: Time,
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        let format = format_description!("[hour]:[minute]");
+        let parse = |time: &str| {
+            Time::parse(time.trim(), format)
+                .map_err(|e| format!("invalid time range '{range}', {e}"))
+        };
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| format!("invalid time range '{range}', expected HH:MM-HH:MM"))?;
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start == end {
+            return Err(format!("invalid time range '{range}', it is empty"));
+        }
+
+        Ok(TimeRange { start, end })
+    }
+}
+
+/// Waybar class names
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Classes {
+    pub ok: String,
+    pub warning: String,
+    pub critical: String,
+    pub paused: String,
+    pub over_budget: String,
+    pub overtime: String,
+    pub error: String,
+}
+
+impl Default for Classes {
+    fn default() -> Self {
+        Classes {
+            ok: STATUS_OK.to_string(),
+            warning: STATUS_WARNING.to_string(),
+            critical: STATUS_CRITICAL.to_string(),
+            paused: STATUS_PAUSED.to_string(),
+            over_budget: STATUS_OVER_BUDGET.to_string(),
+            overtime: STATUS_OVERTIME.to_string(),
+            error: STATUS_ERROR.to_string(),
+        }
+    }
+}
+
+impl Classes {
+    /// Class of an urgency level
+    pub fn for_level(&self, level: Level) -> String {
+        match level {
+            Level::Ok => self.ok.clone(),
+            Level::Warning => self.warning.clone(),
+            Level::Critical => self.critical.clone(),
+        }
+    }
+}
+
+/// Configuration loading errors
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file can't be read
+    Read(PathBuf, std::io::Error),
+    /// The file isn't valid TOML or has unknown keys
+    Parse(PathBuf, toml::de::Error),
+    /// A value is out of range
+    Invalid(String),
+    /// The exercise catalog can't be loaded
+    Catalog(CatalogError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "Can't read {}: {e}", path.display()),
+            ConfigError::Parse(path, e) => write!(f, "Invalid config {}: {e}", path.display()),
+            ConfigError::Invalid(reason) => write!(f, "Invalid configuration: {reason}"),
+            ConfigError::Catalog(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Default configuration file, `$XDG_CONFIG_HOME/burnout-detector/config.toml`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+}
+
+impl Config {
+    /// Read a configuration file
+    pub fn read(path: &Path) -> Result<Config, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.into(), e))?;
+        toml::from_str(&content).map_err(|e| ConfigError::Parse(path.into(), e))
+    }
+
+    /// Read the `--config` file, or the default one if it exists
+    pub fn find(path: Option<&Path>) -> Result<Config, ConfigError> {
+        match path {
+            Some(path) => Config::read(path),
+            None => match default_path() {
+                Some(path) if path.exists() => Config::read(&path),
+                _ => Ok(Config::default()),
+            },
+        }
+    }
+
+    /// Apply the values not given on the command line
+    pub fn merge_into(self, args: &mut Args, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if let (false, Some(idle_timeout)) = (from_cli("idle_timeout"), self.idle_timeout) {
+            args.idle_timeout = idle_timeout;
+        }
+        if let (false, Some(max)) = (from_cli("max_active_sessions"), self.max_active_sessions) {
+            args.max_active_sessions = max;
+        }
+        if let (false, Some(icon)) = (from_cli("icon"), self.icon) {
+            args.icon = icon;
+        }
+        if let (false, Some(waybar)) = (from_cli("waybar") || from_cli("no_waybar"), self.waybar) {
+            args.waybar = waybar;
+        }
+        if let (false, Some(mode)) = (from_cli("mode"), self.mode) {
+            args.mode = mode;
+        }
+        if let (false, Some(source)) = (from_cli("source"), self.source) {
+            args.source = source;
+        }
+        if let (false, Some(fallback)) = (from_cli("fallback_source"), self.fallback_source) {
+            args.fallback_source = Some(fallback);
+        }
+        if let (false, Some(policy)) = (from_cli("seat_policy"), self.seat_policy) {
+            args.seat_policy = policy;
+        }
+        if let (false, Some(logind)) = (from_cli("logind") || from_cli("no_logind"), self.logind) {
+            args.logind = logind;
+        }
+        if let (false, Some(history)) =
+            (from_cli("no_history") || from_cli("history"), self.history)
+        {
+            args.no_history = !history;
+        }
+        if let (false, Some(resume)) = (from_cli("no_resume") || from_cli("resume"), self.resume) {
+            args.no_resume = !resume;
+        }
+        if let (false, Some(path)) = (from_cli("metrics_file"), self.metrics_file) {
+            args.metrics_file = Some(path);
+        }
+        if let (false, Some(enabled)) = (
+            from_cli("no_notify") || from_cli("notify"),
+            self.notification.enabled,
+        ) {
+            args.no_notify = !enabled;
+        }
+        if let Some(timeout) = self.notification.timeout {
+            args.notification_timeout = timeout;
+        }
+        if let Some(stacking) = self.notification.stacking {
+            args.stacking = stacking;
+        }
+        if let Some(classes) = self.classes {
+            args.classes = classes;
+        }
+        if let Some(builtin) = self.exercises.builtin {
+            args.builtin_exercises = builtin;
+        }
+        args.exercises_catalog = self.exercises.catalog;
+        args.exercises_safeeyes = self.exercises.safeeyes;
+        args.exercise_categories = self.exercises.categories;
+        args.schedules = [
+            (ScheduleKind::Micro, self.schedules.micro),
+            (ScheduleKind::Long, self.schedules.long),
+        ]
+        .into_iter()
+        .filter_map(|(kind, config)| Some(ScheduleSettings::new(kind, &config?)))
+        .collect();
+        args.pomodoro = PomodoroSettings::from(&self.pomodoro);
+        args.budget = self.budget.as_ref().map(BudgetSettings::new);
+        args.working_hours = self.working_hours.as_ref().map(WorkingHours::new);
+        if let Some(tooltip) = self.risk.tooltip {
+            args.risk_tooltip = tooltip;
+        }
+        if let Some(weekly_summary) = self.risk.weekly_summary {
+            args.weekly_summary = weekly_summary;
+        }
+    }
+}
+
+/// Longest idle timeout and daily budget, the thresholds are multiples of them
+const MAX_DAY_SECS: u64 = 24 * 3600;
+/// Most eyes shown in waybar
+const MAX_ACTIVE_SESSIONS: u64 = 100;
+
+/// Check the values, whatever they come from
+pub fn validate(args: &Args) -> Result<(), ConfigError> {
+    if args.idle_timeout == 0 || args.idle_timeout > MAX_DAY_SECS {
+        return Err(ConfigError::Invalid(format!(
+            "idle_timeout must be between 1 and {MAX_DAY_SECS} seconds"
+        )));
+    }
+    if args.max_active_sessions == 0 || args.max_active_sessions > MAX_ACTIVE_SESSIONS {
+        return Err(ConfigError::Invalid(format!(
+            "max_active_sessions must be between 1 and {MAX_ACTIVE_SESSIONS}"
+        )));
+    }
+    if args.fallback_source == Some(SourceKind::Auto) {
+        return Err(ConfigError::Invalid(
+            "the fallback source can't be auto".into(),
+        ));
+    }
+    if [
+        &args.classes.ok,
+        &args.classes.warning,
+        &args.classes.critical,
+        &args.classes.paused,
+        &args.classes.over_budget,
+        &args.classes.overtime,
+        &args.classes.error,
+    ]
+    .iter()
+    .any(|class| class.trim().is_empty())
+    {
+        return Err(ConfigError::Invalid("class names can't be empty".into()));
+    }
+    for schedule in &args.schedules {
+        if schedule.duration.is_zero() || schedule.duration >= schedule.interval {
+            return Err(ConfigError::Invalid(format!(
+                "the {} duration must be between 1 second and its interval",
+                schedule.kind
+            )));
+        }
+    }
+    let pomodoro = &args.pomodoro;
+    if [pomodoro.work, pomodoro.short_break, pomodoro.long_break]
+        .iter()
+        .any(Duration::is_zero)
+        || pomodoro.extend.is_some_and(|extend| extend.is_zero())
+        || pomodoro.long_break_after == 0
+    {
+        return Err(ConfigError::Invalid(
+            "the pomodoro durations and long_break_after must be at least 1".into(),
+        ));
+    }
+    if let Some(budget) = &args.budget {
+        if budget.daily.is_zero()
+            || budget.daily > Duration::from_secs(MAX_DAY_SECS)
+            || budget.warning.is_zero()
+            || budget.warning > budget.daily
+        {
+            return Err(ConfigError::Invalid(format!(
+                "the daily budget must be between 1 and {MAX_DAY_SECS} seconds and its warning between 1 and 100%"
+            )));
+        }
+    }
+    if let Some(hours) = &args.working_hours {
+        if hours.repeat.is_zero() {
+            return Err(ConfigError::Invalid(
+                "the overtime notifications repeat must be at least 1 second".into(),
+            ));
+        }
+    }
+    if args.exercises.is_empty() {
+        return Err(ConfigError::Invalid(
+            "the builtin exercises are disabled but the catalog is empty".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Tell when the configuration should be reloaded, on SIGHUP or when the file changes
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    /// Watched configuration file
+    path: Option<PathBuf>,
+    /// Last modification time seen
+    modified: Option<SystemTime>,
+    /// Set by the SIGHUP handler
+    hangup: Arc<AtomicBool>,
+    /// Readable when the configuration directory changes, none when it doesn't exist
+    inotify: Option<OwnedFd>,
+}
+
+impl ConfigWatcher {
+    pub fn new(args: &Args) -> Result<ConfigWatcher, Box<dyn Error>> {
+        let hangup = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&hangup))?;
+        let path = args.config.clone().or_else(default_path);
+        let modified = path.as_deref().and_then(modified);
+        let inotify = path
+            .as_deref()
+            .and_then(Path::parent)
+            .and_then(|dir| watch(dir).ok());
+
+        Ok(ConfigWatcher {
+            path,
+            modified,
+            hangup,
+            inotify,
+        })
+    }
+
+    /// Readable when the configuration may have changed
+    pub fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.inotify.as_ref().map(|inotify| inotify.as_fd())
+    }
+
+    pub fn should_reload(&mut self) -> bool {
+        if let Some(inotify) = &self.inotify {
+            let mut buf = [0; 4096];
+            while matches!(rustix::io::read(inotify, &mut buf[..]), Ok(n) if n > 0) {}
+        }
+        let hangup = self.hangup.swap(false, Ordering::Relaxed);
+        let modified = self.path.as_deref().and_then(modified);
+        let changed = modified != self.modified;
+        self.modified = modified;
+
+        hangup || changed
+    }
+}
+
+/// Watch the files written, moved or removed in `dir`, editors often replace the file
+fn watch(dir: &Path) -> io::Result<OwnedFd> {
+    let inotify = inotify::init(inotify::CreateFlags::NONBLOCK | inotify::CreateFlags::CLOEXEC)?;
+    inotify::add_watch(
+        &inotify,
+        dir,
+        inotify::WatchFlags::CLOSE_WRITE
+            | inotify::WatchFlags::MOVED_TO
+            | inotify::WatchFlags::CREATE
+            | inotify::WatchFlags::DELETE,
+    )?;
+
+    Ok(inotify)
+}
+
+/// Modification time of a file, if it exists
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Build the arguments from the command line and the configuration file
+pub fn load(matches: &ArgMatches) -> Result<Args, ConfigError> {
+    let mut args =
+        Args::from_arg_matches(matches).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    let config = Config::find(args.config.as_deref())?;
+    config.merge_into(&mut args, matches);
+    args.exercises = Catalog::load(
+        args.builtin_exercises,
+        args.exercises_catalog.as_deref(),
+        args.exercises_safeeyes.as_deref(),
+    )
+    .map_err(ConfigError::Catalog)?
+    .exercises;
+    validate(&args)?;
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn load_from(cli: &[&str], config: &str) -> Result<Args, ConfigError> {
+        let matches = Args::command().get_matches_from(cli);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        let config: Config =
+            toml::from_str(config).map_err(|e| ConfigError::Parse("test".into(), e))?;
+        config.merge_into(&mut args, &matches);
+        validate(&args)?;
+        Ok(args)
+    }
+
+    #[test]
+    fn test_config_values_are_used() {
+        let args = load_from(
+            &["burnout-detector"],
+            r#"
+            idle_timeout = 300
+            max_active_sessions = 4
+            icon = "X"
+            waybar = true
+
+            [notification]
+            enabled = false
+            timeout = 5000
+            stacking = "stack"
+
+            [classes]
+            critical = "burning"
+
+            [schedules.micro]
+            interval = 900
+
+            [pomodoro]
+            work = 3000
+            extend = 60
+
+            [budget]
+            daily = 21600
+            day_start = "04:30"
+
+            [working_hours]
+            weekdays = "09:00-18:00"
+            friday = "09:00 - 12:30"
+            sunday = "22:00-02:00"
+
+            [risk]
+            weekly_summary = true
+
+            [exercises]
+            builtin = false
+            catalog = "/tmp/exercises.toml"
+            categories = ["eyes", "breathing"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(args.idle_timeout, 300);
+        assert_eq!(args.max_active_sessions, 4);
+        assert_eq!(args.icon, "X");
+        assert!(args.waybar);
+        assert!(args.no_notify);
+        assert_eq!(args.notification_timeout, 5000);
+        assert_eq!(args.stacking, Stacking::Stack);
+        assert_eq!(args.classes.ok, STATUS_OK);
+        assert_eq!(args.classes.critical, "burning");
+        assert_eq!(args.schedules.len(), 1);
+        assert_eq!(args.schedules[0].kind, ScheduleKind::Micro);
+        assert_eq!(args.schedules[0].interval.as_secs(), 900);
+        assert_eq!(args.pomodoro.work.as_secs(), 3000);
+        assert_eq!(args.pomodoro.short_break.as_secs(), 300);
+        assert_eq!(args.pomodoro.extend, Some(Duration::from_secs(60)));
+        let budget = args.budget.as_ref().unwrap();
+        assert_eq!(budget.day_start, time::macros::time!(04:30));
+        assert_eq!(budget.warning, Duration::from_secs(21600 * 9 / 10));
+        let hours = args.working_hours.as_ref().unwrap();
+        assert_eq!(hours.days[0].unwrap().end, time::macros::time!(18:00));
+        assert_eq!(hours.days[4].unwrap().end, time::macros::time!(12:30));
+        assert_eq!(hours.days[5], None);
+        assert_eq!(hours.days[6].unwrap().end, time::macros::time!(02:00));
+        assert!(args.risk_tooltip);
+        assert!(args.weekly_summary);
+        assert!(!args.builtin_exercises);
+        assert_eq!(
+            args.exercise_categories,
+            vec![Category::Eyes, Category::Breathing]
+        );
+        assert_eq!(
+            args.exercises_catalog,
+            Some(PathBuf::from("/tmp/exercises.toml"))
+        );
+    }
+
+    #[test]
+    fn test_cli_takes_precedence_over_config() {
+        let args = load_from(
+            &["burnout-detector", "--idle-timeout", "120", "--icon", "O"],
+            "idle_timeout = 300\nicon = \"X\"\nmax_active_sessions = 5\nmode = \"pomodoro\"",
+        )
+        .unwrap();
+
+        assert_eq!(args.mode, Mode::Pomodoro);
+        assert_eq!(args.idle_timeout, 120);
+        assert_eq!(args.icon, "O");
+        assert_eq!(args.max_active_sessions, 5);
+
+        let args = load_from(
+            &["burnout-detector", "--fallback-source", "stdin"],
+            "fallback_source = \"x11\"",
+        )
+        .unwrap();
+        assert_eq!(args.fallback_source, Some(SourceKind::Stdin));
+        assert_eq!(args.seat_policy, SeatPolicy::AnyActive);
+
+        let args = load_from(&["burnout-detector"], "seat_policy = \"all-active\"").unwrap();
+        assert_eq!(args.seat_policy, SeatPolicy::AllActive);
+
+        let args = load_from(
+            &["burnout-detector", "--no-waybar", "--no-logind", "--history", "--notify"],
+            "waybar = true\nlogind = true\nhistory = false\nresume = false\n[notification]\nenabled = false",
+        )
+        .unwrap();
+        assert!(!args.waybar);
+        assert!(!args.logind);
+        assert!(!args.no_history);
+        assert!(args.no_resume);
+        assert!(!args.no_notify);
+
+        let args = load_from(&["burnout-detector", "--no-resume", "--resume"], "").unwrap();
+        assert!(!args.no_resume);
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        assert!(matches!(
+            load_from(&["burnout-detector"], "idle_timeout = 0"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector", "-m", "0"], ""),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "[pomodoro]\nlong_break_after = 0"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "[budget]\nday_start = \"25:00\""),
+            Err(ConfigError::Parse(_, _))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "[budget]\nwarning = 120"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(
+                &["burnout-detector"],
+                "[working_hours]\nmonday = \"09:00-09:00\""
+            ),
+            Err(ConfigError::Parse(_, _))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "idle_timeout = 9223372036854775807"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector", "-m", "18446744073709551615"], ""),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(
+                &["burnout-detector"],
+                "[budget]\ndaily = 9223372036854775807\nwarning = 4294967295"
+            ),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "fallback_source = \"auto\""),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "idle_timout = 10"),
+            Err(ConfigError::Parse(_, _))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "idle_timeout = -1"),
+            Err(ConfigError::Parse(_, _))
+        ));
+        assert!(matches!(
+            load_from(
+                &["burnout-detector"],
+                "[schedules.long]\ninterval = 600\nduration = 900"
+            ),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_from(&["burnout-detector"], "[exercises]\nextra = [\"Walk\"]"),
+            Err(ConfigError::Parse(_, _))
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+use clap::Subcommand;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::helpers::parse_duration;
+
+/// Socket file name in `$XDG_RUNTIME_DIR`
+const SOCKET_FILE: &str = "burnout-detector.sock";
+/// Time given to a client to send its command or read the reply
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Replies starting with this prefix are errors
+const ERROR_PREFIX: &str = "error: ";
+
+/// Commands understood by the running daemon
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Show the current session
+    Status,
+    /// Stop tracking, until `resume` or for the given duration (e.g. 30m, 1h)
+    Pause {
+        #[arg(value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// End a pause or a break
+    Resume,
+    /// Delay the next notification
+    Snooze {
+        #[arg(default_value = "15m", value_parser = parse_duration)]
+        duration: Duration,
+    },
+    /// Start a new active session from zero
+    Reset,
+    /// Start a break right now
+    BreakNow,
+}
+
+impl fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlCommand::Status => write!(f, "status"),
+            ControlCommand::Pause { duration: None } => write!(f, "pause"),
+            ControlCommand::Pause {
+                duration: Some(duration),
+            } => write!(f, "pause {}s", duration.as_secs()),
+            ControlCommand::Resume => write!(f, "resume"),
+            ControlCommand::Snooze { duration } => write!(f, "snooze {}s", duration.as_secs()),
+            ControlCommand::Reset => write!(f, "reset"),
+            ControlCommand::BreakNow => write!(f, "break-now"),
+        }
+    }
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let command = words.next().unwrap_or_default().to_lowercase();
+        let duration = words.next().map(parse_duration).transpose()?;
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected argument '{extra}'"));
+        }
+
+        match (command.as_str(), duration) {
+            ("status", None) => Ok(ControlCommand::Status),
+            ("pause", duration) => Ok(ControlCommand::Pause { duration }),
+            ("resume", None) => Ok(ControlCommand::Resume),
+            ("snooze", duration) => Ok(ControlCommand::Snooze {
+                duration: duration.unwrap_or(Duration::from_secs(15 * 60)),
+            }),
+            ("reset", None) => Ok(ControlCommand::Reset),
+            ("break-now", None) => Ok(ControlCommand::BreakNow),
+            ("status" | "resume" | "reset" | "break-now", Some(_)) => {
+                Err(format!("'{command}' doesn't take a duration"))
+            }
+            _ => Err(format!("unknown command '{}'", s.trim())),
+        }
+    }
+}
+
+/// Control socket, `$XDG_RUNTIME_DIR/burnout-detector.sock`
+pub fn socket_path() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join(SOCKET_FILE))
+}
+
+/// Listening side of the control socket, owned by the daemon
+#[derive(Debug)]
+pub struct ControlServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlServer {
+    /// Listen on `path`, replacing a stale socket left by a crashed daemon
+    pub fn bind(path: &Path) -> Result<ControlServer, Box<dyn Error>> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(format!("{} is used by another daemon", path.display()).into());
+            }
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(ControlServer {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Readable when a client is waiting
+    pub fn fd(&self) -> BorrowedFd<'_> {
+        self.listener.as_fd()
+    }
+
+    /// Answer the pending clients, without blocking when there is none
+    pub fn handle_pending<F>(&self, mut handle: F)
+    where
+        F: FnMut(ControlCommand) -> Result<String, String>,
+    {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                Err(e) => {
+                    eprintln!("Can't accept a control client: {e}");
+                    return;
+                }
+            };
+            if let Err(e) = serve(stream, &mut handle) {
+                eprintln!("Can't answer the control client: {e}");
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Read one command line and write the reply
+fn serve<F>(stream: UnixStream, handle: &mut F) -> io::Result<()>
+where
+    F: FnMut(ControlCommand) -> Result<String, String>,
+{
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let reply = match line.parse() {
+        Ok(command) => handle(command),
+        Err(e) => Err(e),
+    };
+    let reply = reply.unwrap_or_else(|e| format!("{ERROR_PREFIX}{e}"));
+
+    (&stream).write_all(format!("{reply}\n").as_bytes())
+}
+
+/// Send a command to the daemon listening on `path` and return its reply
+pub fn send(path: &Path, command: &ControlCommand) -> Result<String, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(path).map_err(|e| {
+        format!(
+            "Can't connect to {}, is the daemon running? {e}",
+            path.display()
+        )
+    })?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT * 5))?;
+    stream.write_all(format!("{command}\n").as_bytes())?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    let reply = reply.trim_end().to_string();
+    match reply.strip_prefix(ERROR_PREFIX) {
+        Some(e) => Err(e.into()),
+        None => Ok(reply),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("status".parse(), Ok(ControlCommand::Status));
+        assert_eq!(
+            "pause".parse(),
+            Ok(ControlCommand::Pause { duration: None })
+        );
+        assert_eq!(
+            "pause 1h".parse(),
+            Ok(ControlCommand::Pause {
+                duration: Some(Duration::from_secs(3600))
+            })
+        );
+        assert_eq!(
+            "snooze\n".parse(),
+            Ok(ControlCommand::Snooze {
+                duration: Duration::from_secs(900)
+            })
+        );
+        assert_eq!("break-now".parse(), Ok(ControlCommand::BreakNow));
+        assert!("reset 5m".parse::<ControlCommand>().is_err());
+        assert!("snooze 5m 10m".parse::<ControlCommand>().is_err());
+        assert!("sleep".parse::<ControlCommand>().is_err());
+
+        // the client sends the Display form
+        let command = ControlCommand::Snooze {
+            duration: Duration::from_secs(600),
+        };
+        assert_eq!(command.to_string().parse(), Ok(command));
+    }
+
+    #[test]
+    fn test_send_commands_to_the_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SOCKET_FILE);
+        let server = ControlServer::bind(&path).unwrap();
+        assert!(ControlServer::bind(&path).is_err());
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            (
+                send(&client_path, &ControlCommand::Status).map_err(|e| e.to_string()),
+                send(&client_path, &ControlCommand::Reset).map_err(|e| e.to_string()),
+            )
+        });
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            server.handle_pending(|command| {
+                received.push(command.clone());
+                match command {
+                    ControlCommand::Status => Ok("Active for 00:10:00".into()),
+                    _ => Err("not now".into()),
+                }
+            });
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let (status, reset) = client.join().unwrap();
+        assert_eq!(status.unwrap(), "Active for 00:10:00");
+        assert_eq!(reset.unwrap_err(), "not now");
+        assert_eq!(
+            received,
+            vec![ControlCommand::Status, ControlCommand::Reset]
+        );
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
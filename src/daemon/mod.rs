@@ -0,0 +1,387 @@
+use clap::ArgMatches;
+use rustix::event::{poll, PollFd, PollFlags, Timespec};
+use std::io::{self, Read};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, ConfigWatcher};
+use crate::control::{self, ControlServer};
+use crate::helpers::lock::{self, InstanceLock};
+use crate::history::checkpoint::{self, Checkpoint};
+use crate::logic::{self, Logic};
+use crate::metrics;
+use crate::source::{self, IdleSource};
+
+/// Interval between two checkpoints of the current session
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+/// Interval between two writes of the metrics file
+const METRICS_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Socket readable when SIGTERM, SIGINT or SIGHUP is received
+fn signal_socket() -> io::Result<UnixStream> {
+    let (reader, writer) = UnixStream::pair()?;
+    reader.set_nonblocking(true)?;
+    for signal in [
+        signal_hook::consts::SIGTERM,
+        signal_hook::consts::SIGINT,
+        signal_hook::consts::SIGHUP,
+    ] {
+        signal_hook::low_level::pipe::register(signal, writer.try_clone()?)?;
+    }
+
+    Ok(reader)
+}
+
+/// Read everything available on a non-blocking socket
+fn drain(mut socket: &UnixStream) {
+    let mut buf = [0; 64];
+    while matches!(socket.read(&mut buf), Ok(n) if n > 0) {}
+}
+
+/// Sleep until one of `fds` is readable or `timeout` elapsed
+fn wait(fds: &[BorrowedFd<'_>], timeout: Duration) {
+    let mut fds: Vec<_> = fds
+        .iter()
+        .map(|fd| PollFd::from_borrowed_fd(*fd, PollFlags::IN))
+        .collect();
+    let timespec = Timespec::try_from(timeout).unwrap_or_default();
+    match poll(&mut fds, Some(&timespec)) {
+        Ok(_) | Err(rustix::io::Errno::INTR) => {}
+        Err(e) => {
+            eprintln!("Can't wait for the events: {e}");
+            thread::sleep(timeout);
+        }
+    }
+}
+
+/// Main loop feeding the idle source events into the logic
+pub struct Daemon {
+    /// Idle events sources
+    sources: Vec<Box<dyn IdleSource>>,
+    /// Logic struct
+    logic: Logic,
+    /// Command line, merged again with the file on reload
+    matches: ArgMatches,
+    /// Where the current session is checkpointed
+    checkpoint: Option<PathBuf>,
+    /// The fallback source replaced an unavailable one already
+    fell_back: bool,
+    /// Last write of the metrics file
+    metrics_written: Option<Instant>,
+    /// Held while this instance writes the state files, none when it is read-only
+    lock: Option<InstanceLock>,
+    /// This instance writes the history, the checkpoint and the metrics
+    writer: bool,
+}
+
+impl Daemon {
+    pub fn new(sources: Vec<Box<dyn IdleSource>>, logic: Logic, matches: ArgMatches) -> Daemon {
+        let checkpoint = if logic.args.no_resume {
+            None
+        } else {
+            checkpoint::default_path()
+        };
+
+        Daemon {
+            sources,
+            logic,
+            matches,
+            checkpoint,
+            fell_back: false,
+            metrics_written: None,
+            lock: None,
+            writer: false,
+        }
+    }
+
+    /// Continue the session saved by the previous run
+    fn resume(&mut self) {
+        let Some(checkpoint) = self.checkpoint.as_deref().and_then(Checkpoint::load) else {
+            return;
+        };
+        if self.logic.resume(checkpoint) {
+            eprintln!("Resuming the previous session");
+        } else {
+            // the stale session is in the history now, a restart must not record it again
+            self.save_checkpoint();
+        }
+    }
+
+    /// Take the lock of the state files, the other instances stay read-only until it is released
+    fn take_lock(&mut self) {
+        if self.writer {
+            return;
+        }
+        let Some(path) = lock::default_path() else {
+            // no state directory, nothing is shared
+            self.writer = true;
+            return;
+        };
+        match InstanceLock::acquire(&path) {
+            Ok(Some(lock)) => {
+                self.lock = Some(lock);
+                self.writer = true;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Can't lock the state files, writing them anyway: {e}");
+                self.writer = true;
+            }
+        }
+        if let Some(history) = self.logic.history.as_mut() {
+            history.read_only = !self.writer;
+        }
+    }
+
+    fn save_checkpoint(&self) {
+        if !self.writer {
+            return;
+        }
+        let Some(path) = self.checkpoint.as_deref() else {
+            return;
+        };
+        if let Err(e) = self.logic.checkpoint().save(path) {
+            eprintln!("Can't save the session checkpoint: {e}");
+        }
+    }
+
+    /// Reload the configuration, the current one is kept if the new one is invalid
+    fn reload(&mut self) {
+        let args = match config::load(&self.matches) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("{e}, keeping the current configuration");
+                return;
+            }
+        };
+        eprintln!("Reloading the configuration");
+        if args.debug {
+            eprintln!("Params: {args:?}");
+        }
+
+        for source in self.sources.iter_mut() {
+            if let Err(e) = source.reload(&args) {
+                eprintln!("Can't reload the {} idle source: {e}", source.name());
+            }
+        }
+        self.logic.reload(&args);
+    }
+
+    /// Replace the source unable to detect the idle time by the fallback one, once
+    fn fall_back(&mut self) {
+        let Some(fallback) = self.logic.args.fallback_source.filter(|_| !self.fell_back) else {
+            return;
+        };
+        let Some(index) = self.sources.iter().position(|source| source.unavailable()) else {
+            return;
+        };
+        self.fell_back = true;
+
+        let source = source::build(fallback, &self.logic.args).and_then(|mut source| {
+            source.init()?;
+            Ok(source)
+        });
+        match source {
+            Ok(source) => {
+                eprintln!(
+                    "Falling back from the {} to the {} idle source",
+                    self.sources[index].name(),
+                    source.name()
+                );
+                self.sources[index] = source;
+            }
+            Err(e) => eprintln!("Can't fall back to the {fallback} idle source: {e}"),
+        }
+    }
+
+    /// Time before the metrics file is written again, none when disabled
+    fn next_metrics(&self) -> Option<Duration> {
+        self.logic.args.metrics_file.as_ref()?;
+        let elapsed = self
+            .metrics_written
+            .map_or(METRICS_INTERVAL, |last| last.elapsed());
+
+        Some(METRICS_INTERVAL.saturating_sub(elapsed))
+    }
+
+    /// Write the metrics file when due
+    fn write_metrics(&mut self) {
+        let Some(path) = self.logic.args.metrics_file.clone().filter(|_| self.writer) else {
+            return;
+        };
+        if self.next_metrics().is_some_and(|next| !next.is_zero()) {
+            return;
+        }
+        if let Err(e) = metrics::write(&path, &mut self.logic) {
+            eprintln!("Can't write the metrics to {}: {e}", path.display());
+        }
+        self.metrics_written = Some(Instant::now());
+    }
+
+    /// Time before the logic, a polling source, the checkpoint or the metrics need to run
+    fn next_wakeup(&self, last_checkpoint: Instant) -> Duration {
+        self.sources
+            .iter()
+            .filter_map(|source| source.next_dispatch())
+            .chain([
+                self.logic.next_deadline(),
+                CHECKPOINT_INTERVAL.saturating_sub(last_checkpoint.elapsed()),
+            ])
+            .chain(self.next_metrics())
+            .min()
+            .unwrap_or(CHECKPOINT_INTERVAL)
+    }
+
+    pub fn run(mut self) -> ! {
+        let mut watcher = ConfigWatcher::new(&self.logic.args)
+            .map_err(|e| eprintln!("Can't watch the configuration, it won't be reloaded: {e}"))
+            .ok();
+        let terminate = Arc::new(AtomicBool::new(false));
+        let signals = [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT]
+            .into_iter()
+            .try_for_each(|signal| {
+                signal_hook::flag::register(signal, Arc::clone(&terminate)).map(|_| ())
+            })
+            .and_then(|()| signal_socket());
+        let signals = match signals {
+            Ok(signals) => signals,
+            Err(e) => logic::exit_with_error(
+                &self.logic.args,
+                &format!("Can't register the signal handlers: {e}"),
+            ),
+        };
+        let control = match control::socket_path().map(|path| ControlServer::bind(&path)) {
+            Some(Ok(control)) => Some(control),
+            Some(Err(e)) => {
+                eprintln!("Can't listen on the control socket: {e}");
+                None
+            }
+            None => {
+                eprintln!("XDG_RUNTIME_DIR is not set, the control socket is disabled");
+                None
+            }
+        };
+        self.take_lock();
+        if !self.writer {
+            eprintln!("Another instance writes the history, this one is read-only");
+        }
+        self.resume();
+        let mut last_checkpoint = Instant::now();
+
+        for source in self.sources.iter_mut() {
+            if let Err(e) = source.init() {
+                logic::exit_with_error(
+                    &self.logic.args,
+                    &format!("Can't initialize the {} idle source: {e}", source.name()),
+                );
+            }
+            eprintln!("Using the {} idle source", source.name());
+        }
+
+        // run on every event and deadline, then sleep until the next one
+        loop {
+            drain(&signals);
+            if terminate.load(Ordering::Relaxed) {
+                self.save_checkpoint();
+                drop(control);
+                std::process::exit(0);
+            }
+            if watcher.as_mut().is_some_and(ConfigWatcher::should_reload) {
+                self.reload();
+            }
+            if let Some(control) = control.as_ref() {
+                control.handle_pending(|command| self.logic.control(command));
+            }
+            let mut problems = Vec::new();
+            for source in self.sources.iter_mut() {
+                match source.dispatch() {
+                    Ok(events) => {
+                        for event in events {
+                            self.logic.handle_event(event);
+                        }
+                    }
+                    Err(e) => problems.push(format!("{} idle source: {e}", source.name())),
+                }
+            }
+            self.fall_back();
+            problems.extend(self.sources.iter().filter_map(|source| source.degraded()));
+            self.logic
+                .set_degraded((!problems.is_empty()).then(|| problems.join("\n")));
+            if let Err(e) = self.logic.run() {
+                // waybar is gone when its output can't be written
+                eprintln!("{e}, exiting");
+                self.save_checkpoint();
+                drop(control);
+                std::process::exit(1);
+            }
+            if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                // take over once the writing instance is gone
+                if !self.writer {
+                    self.take_lock();
+                    if self.writer {
+                        eprintln!("The other instance is gone, writing the history from now on");
+                    }
+                }
+                self.save_checkpoint();
+                last_checkpoint = Instant::now();
+            }
+            self.write_metrics();
+
+            let timeout = self.next_wakeup(last_checkpoint);
+            let fds: Vec<_> = [
+                Some(signals.as_fd()),
+                watcher.as_ref().and_then(ConfigWatcher::fd),
+                self.logic.alert.fd(),
+            ]
+            .into_iter()
+            .chain([control.as_ref().map(ControlServer::fd)])
+            .chain(self.sources.iter().map(|source| source.fd()))
+            .flatten()
+            .collect();
+            wait(&fds, timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{History, SessionKind};
+    use crate::Args;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_stale_checkpoint_is_recorded_once() {
+        let args = Args {
+            idle_timeout: 60,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+
+        let mut logic = Logic::new(&args).unwrap();
+        let mut checkpoint = logic.checkpoint();
+        checkpoint.start -= time::Duration::minutes(10);
+        checkpoint.saved_at -= time::Duration::minutes(2);
+        checkpoint.save(&path).unwrap();
+
+        logic.history = Some(History::new(dir.path()));
+        let matches = Args::command().get_matches_from(["burnout-detector"]);
+        let mut daemon = Daemon::new(Vec::new(), logic, matches);
+        daemon.checkpoint = Some(path);
+        daemon.writer = true;
+        // like two restarts in a row
+        daemon.resume();
+        daemon.resume();
+
+        let records = daemon.logic.history.as_ref().unwrap().read_all().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, SessionKind::Active);
+        assert_eq!(records[0].duration_secs, 480);
+    }
+}
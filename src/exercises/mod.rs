@@ -0,0 +1,358 @@
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub mod safeeyes;
+pub mod selector;
+
+/// Exercises shipped with the binary
+const BUILTIN_CATALOG: &str = include_str!("default.toml");
+
+/// Kind of exercise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Eyes,
+    Stretching,
+    Breathing,
+    Mental,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Eyes => write!(f, "eyes"),
+            Category::Stretching => write!(f, "stretching"),
+            Category::Breathing => write!(f, "breathing"),
+            Category::Mental => write!(f, "mental"),
+        }
+    }
+}
+
+/// Exercise suggested in the notifications
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Exercise {
+    /// What to do
+    pub text: String,
+    /// Guide opened from the notification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Notification icon name or image path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// How long it takes, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    /// Eyes, stretching, breathing or mental
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
+    /// Free form labels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// How often it is picked compared to the others, 1 by default, 0 never
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+}
+
+impl fmt::Display for Exercise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.duration {
+            Some(duration) => write!(f, "{} ({duration}s)", self.text),
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
+
+impl Exercise {
+    /// Problems found in the exercise
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.text.trim().is_empty() {
+            problems.push("the text is empty".to_string());
+        }
+        if let Some(url) = &self.url {
+            if !["http://", "https://", "file://"]
+                .iter()
+                .any(|scheme| url.starts_with(scheme))
+            {
+                problems.push(format!("'{url}' is not an http(s) or file URL"));
+            }
+        }
+        if self
+            .icon
+            .as_deref()
+            .is_some_and(|icon| icon.trim().is_empty())
+        {
+            problems.push("the icon is empty".to_string());
+        }
+        if self.duration == Some(0) {
+            problems.push("the duration must be at least 1 second".to_string());
+        }
+        problems
+    }
+}
+
+/// List of exercises, `[[exercises]]` tables in TOML or an `exercises` array in JSON
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Catalog {
+    pub exercises: Vec<Exercise>,
+}
+
+/// Catalog loading errors
+#[derive(Debug)]
+pub enum CatalogError {
+    /// The file can't be read
+    Read(PathBuf, std::io::Error),
+    /// The file isn't a valid catalog
+    Parse(PathBuf, String),
+    /// Some exercises are invalid
+    Invalid(PathBuf, Vec<String>),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::Read(path, e) => write!(f, "Can't read {}: {e}", path.display()),
+            CatalogError::Parse(path, e) => {
+                write!(f, "Invalid exercise catalog {}: {e}", path.display())
+            }
+            CatalogError::Invalid(path, problems) => write!(
+                f,
+                "Invalid exercise catalog {}:\n  {}",
+                path.display(),
+                problems.join("\n  ")
+            ),
+        }
+    }
+}
+
+impl Error for CatalogError {}
+
+/// Default user catalog, `$XDG_CONFIG_HOME/burnout-detector/exercises.toml`,
+/// or `exercises.json` when only this one exists
+pub fn default_path() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join(env!("CARGO_PKG_NAME"));
+    let json = dir.join("exercises.json");
+    if json.exists() && !dir.join("exercises.toml").exists() {
+        return Some(json);
+    }
+    Some(dir.join("exercises.toml"))
+}
+
+impl Catalog {
+    /// Exercises shipped with the binary
+    pub fn builtin() -> Catalog {
+        Catalog::parse(BUILTIN_CATALOG, false).expect("The builtin catalog is valid")
+    }
+
+    fn parse(content: &str, json: bool) -> Result<Catalog, String> {
+        if json {
+            serde_json::from_str(content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(content).map_err(|e| e.to_string())
+        }
+    }
+
+    /// Read and check a catalog, JSON when the file has a `.json` extension, TOML otherwise
+    pub fn read(path: &Path) -> Result<Catalog, CatalogError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| CatalogError::Read(path.into(), e))?;
+        let json = path
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        let catalog =
+            Catalog::parse(&content, json).map_err(|e| CatalogError::Parse(path.into(), e))?;
+        let problems = catalog.problems();
+        if !problems.is_empty() {
+            return Err(CatalogError::Invalid(path.into(), problems));
+        }
+
+        Ok(catalog)
+    }
+
+    /// Problems of every exercise, prefixed with its position
+    pub fn problems(&self) -> Vec<String> {
+        self.exercises
+            .iter()
+            .enumerate()
+            .flat_map(|(index, exercise)| {
+                exercise
+                    .problems()
+                    .into_iter()
+                    .map(move |problem| format!("exercise {}: {problem}", index + 1))
+            })
+            .collect()
+    }
+
+    /// Add the exercises of `other`, the ones with the same text are overridden
+    pub fn extend(&mut self, other: Catalog) {
+        for exercise in other.exercises {
+            match self.exercises.iter_mut().find(|e| e.text == exercise.text) {
+                Some(existing) => *existing = exercise,
+                None => self.exercises.push(exercise),
+            }
+        }
+    }
+
+    /// Builtin exercises, extended with the SafeEyes breaks and the user catalog,
+    /// or replaced when `builtin` is false
+    pub fn load(
+        builtin: bool,
+        path: Option<&Path>,
+        safeeyes: Option<&Path>,
+    ) -> Result<Catalog, CatalogError> {
+        let mut catalog = if builtin {
+            Catalog::builtin()
+        } else {
+            Catalog::default()
+        };
+        if let Some(safeeyes) = safeeyes {
+            catalog.extend(safeeyes::import(safeeyes)?);
+        }
+        match path {
+            Some(path) => catalog.extend(Catalog::read(path)?),
+            None => {
+                if let Some(path) = default_path().filter(|path| path.exists()) {
+                    catalog.extend(Catalog::read(&path)?);
+                }
+            }
+        }
+
+        Ok(catalog)
+    }
+}
+
+/// `exercises` subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum ExercisesCommand {
+    /// List the exercises in use
+    List,
+    /// Check a catalog file, the user one by default
+    Validate { path: Option<PathBuf> },
+    /// Print the breaks of a SafeEyes configuration as a catalog,
+    /// ~/.config/safeeyes/safeeyes.json by default
+    ImportSafeeyes { path: Option<PathBuf> },
+}
+
+/// Run an `exercises` subcommand
+pub fn run(exercises: &[Exercise], command: &ExercisesCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        ExercisesCommand::List => {
+            for exercise in exercises {
+                let category = exercise.category.map(|c| c.to_string());
+                let duration = exercise.duration.map(|d| format!("{d}s"));
+                println!(
+                    "{:<12}{:<7}{}{}",
+                    category.unwrap_or_default(),
+                    duration.unwrap_or_default(),
+                    exercise.text,
+                    exercise
+                        .url
+                        .as_deref()
+                        .map(|url| format!(" <{url}>"))
+                        .unwrap_or_default()
+                );
+            }
+        }
+        ExercisesCommand::Validate { path } => {
+            let path = path
+                .clone()
+                .or_else(default_path)
+                .ok_or("Can't find the configuration directory")?;
+            let catalog = Catalog::read(&path)?;
+            println!(
+                "{}: {} valid exercise(s)",
+                path.display(),
+                catalog.exercises.len()
+            );
+        }
+        ExercisesCommand::ImportSafeeyes { path } => {
+            let path = path
+                .clone()
+                .or_else(safeeyes::default_path)
+                .ok_or("Can't find the configuration directory")?;
+            let catalog = safeeyes::import(&path)?;
+            print!("{}", toml::to_string(&catalog)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog_is_valid() {
+        let catalog = Catalog::builtin();
+        assert!(catalog.exercises.len() >= 50);
+        assert!(catalog.problems().is_empty());
+        assert!(catalog
+            .exercises
+            .iter()
+            .all(|exercise| exercise.category.is_some()));
+    }
+
+    #[test]
+    fn test_user_catalog_extends_the_builtin_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exercises.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [[exercises]]
+            text = "Do 10 squats 🏋️"
+            duration = 60
+            category = "stretching"
+            tags = ["physio"]
+            weight = 3
+
+            [[exercises]]
+            text = "Blink your eyes 😉"
+            url = "https://example.com/blink"
+            "#,
+        )
+        .unwrap();
+
+        let builtin = Catalog::builtin();
+        let catalog = Catalog::load(true, Some(&path), None).unwrap();
+        assert_eq!(catalog.exercises.len(), builtin.exercises.len() + 1);
+        let blink = catalog
+            .exercises
+            .iter()
+            .find(|exercise| exercise.text == "Blink your eyes 😉")
+            .unwrap();
+        assert_eq!(blink.url.as_deref(), Some("https://example.com/blink"));
+
+        let catalog = Catalog::load(false, Some(&path), None).unwrap();
+        assert_eq!(catalog.exercises.len(), 2);
+        assert_eq!(catalog.exercises[0].tags, vec!["physio".to_string()]);
+        assert_eq!(catalog.exercises[0].weight, Some(3));
+    }
+
+    #[test]
+    fn test_invalid_catalogs_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exercises.json");
+        std::fs::write(
+            &path,
+            r#"{"exercises": [{"text": "Walk"}, {"text": " ", "url": "google.com", "duration": 0}]}"#,
+        )
+        .unwrap();
+        match Catalog::read(&path) {
+            Err(CatalogError::Invalid(_, problems)) => assert_eq!(problems.len(), 3),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        std::fs::write(&path, r#"{"exercises": [{"txt": "Walk"}]}"#).unwrap();
+        assert!(matches!(
+            Catalog::read(&path),
+            Err(CatalogError::Parse(_, _))
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::exercises::{Catalog, CatalogError, Category, Exercise};
+
+/// Default SafeEyes short break duration, in seconds
+const SHORT_BREAK_DURATION: u64 = 15;
+/// Default SafeEyes long break duration, in seconds
+const LONG_BREAK_DURATION: u64 = 60;
+
+/// Part of `safeeyes.json` describing the breaks, the other settings are ignored
+#[derive(Deserialize, Debug)]
+struct SafeEyesConfig {
+    short_break_duration: Option<u64>,
+    long_break_duration: Option<u64>,
+    #[serde(default)]
+    short_breaks: Vec<SafeEyesBreak>,
+    #[serde(default)]
+    long_breaks: Vec<SafeEyesBreak>,
+}
+
+/// SafeEyes break
+#[derive(Deserialize, Debug)]
+struct SafeEyesBreak {
+    name: String,
+    /// Image file, relative to the configuration directory
+    image: Option<String>,
+    /// Overrides the default break duration
+    duration: Option<u64>,
+}
+
+/// User SafeEyes configuration, `$XDG_CONFIG_HOME/safeeyes/safeeyes.json`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("safeeyes").join("safeeyes.json"))
+}
+
+impl SafeEyesBreak {
+    fn into_exercise(self, dir: &Path, duration: u64, category: Category, kind: &str) -> Exercise {
+        let icon = self.image.map(|image| {
+            let path = dir.join(&image);
+            if path.exists() {
+                path.display().to_string()
+            } else {
+                image
+            }
+        });
+
+        Exercise {
+            text: self.name.trim().to_string(),
+            url: None,
+            icon,
+            duration: Some(self.duration.unwrap_or(duration)),
+            category: Some(category),
+            tags: vec!["safeeyes".to_string(), kind.to_string()],
+            weight: None,
+        }
+    }
+}
+
+/// Convert the SafeEyes short breaks into eyes exercises and the long breaks into stretching ones
+fn convert(content: &str, dir: &Path) -> Result<Catalog, String> {
+    let config: SafeEyesConfig = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let short_duration = config.short_break_duration.unwrap_or(SHORT_BREAK_DURATION);
+    let long_duration = config.long_break_duration.unwrap_or(LONG_BREAK_DURATION);
+
+    let short = config
+        .short_breaks
+        .into_iter()
+        .map(|b| b.into_exercise(dir, short_duration, Category::Eyes, "short"));
+    let long = config
+        .long_breaks
+        .into_iter()
+        .map(|b| b.into_exercise(dir, long_duration, Category::Stretching, "long"));
+
+    Ok(Catalog {
+        exercises: short.chain(long).collect(),
+    })
+}
+
+/// Read the breaks of a SafeEyes configuration
+pub fn import(path: &Path) -> Result<Catalog, CatalogError> {
+    let content = std::fs::read_to_string(path).map_err(|e| CatalogError::Read(path.into(), e))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let catalog = convert(&content, dir).map_err(|e| CatalogError::Parse(path.into(), e))?;
+    let problems = catalog.problems();
+    if !problems.is_empty() {
+        return Err(CatalogError::Invalid(path.into(), problems));
+    }
+
+    Ok(catalog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_safeeyes_breaks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("walk.png"), b"").unwrap();
+        let content = r#"{
+            "short_break_duration": 20,
+            "long_breaks": [
+                {"name": "Walk for a while", "image": "walk.png", "duration": 120},
+                {"name": "Lean back at your seat and relax", "plugins": []}
+            ],
+            "short_breaks": [
+                {"name": "Tightly close your eyes"},
+                {"name": "Roll your eyes a few times to each side", "image": "missing.png"}
+            ],
+            "plugins": [{"id": "audiblealert", "enabled": true}]
+        }"#;
+
+        let catalog = convert(content, dir.path()).unwrap();
+        let summary: Vec<_> = catalog
+            .exercises
+            .iter()
+            .map(|e| (e.text.as_str(), e.duration, e.category))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Tightly close your eyes", Some(20), Some(Category::Eyes)),
+                (
+                    "Roll your eyes a few times to each side",
+                    Some(20),
+                    Some(Category::Eyes)
+                ),
+                ("Walk for a while", Some(120), Some(Category::Stretching)),
+                (
+                    "Lean back at your seat and relax",
+                    Some(LONG_BREAK_DURATION),
+                    Some(Category::Stretching)
+                ),
+            ]
+        );
+        assert_eq!(
+            catalog.exercises[2].icon,
+            Some(dir.path().join("walk.png").display().to_string())
+        );
+        assert_eq!(catalog.exercises[1].icon.as_deref(), Some("missing.png"));
+        assert_eq!(catalog.exercises[2].tags, vec!["safeeyes", "long"]);
+
+        // the converted catalog can be written as a user one
+        let toml = toml::to_string(&catalog).unwrap();
+        assert_eq!(toml::from_str::<Catalog>(&toml).unwrap(), catalog);
+    }
+}
@@ -0,0 +1,228 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exercises::{Category, Exercise};
+use crate::helpers::{state_dir, write_atomic};
+
+/// Number of recently shown exercises avoided by the next picks
+const RECENT_EXERCISES: usize = 10;
+
+/// Recently shown exercises in `$XDG_STATE_HOME/burnout-detector`
+pub fn default_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("recent_exercises.json"))
+}
+
+/// Weighted exercise picker avoiding the recently shown ones
+#[derive(Debug)]
+pub struct Selector {
+    rng: StdRng,
+    /// Texts of the last shown exercises, the most recent last
+    recent: VecDeque<String>,
+    /// Where the recent exercises are persisted
+    path: Option<PathBuf>,
+}
+
+impl Default for Selector {
+    fn default() -> Self {
+        Selector {
+            rng: StdRng::from_entropy(),
+            recent: VecDeque::new(),
+            path: None,
+        }
+    }
+}
+
+impl Selector {
+    /// Deterministic selector, for the tests
+    #[cfg(test)]
+    pub fn seeded(seed: u64) -> Selector {
+        Selector {
+            rng: StdRng::seed_from_u64(seed),
+            ..Selector::default()
+        }
+    }
+
+    /// Remember the recent exercises in `path`, across the restarts
+    pub fn persistent(path: &Path) -> Selector {
+        let recent = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Selector {
+            recent,
+            path: Some(path.to_path_buf()),
+            ..Selector::default()
+        }
+    }
+
+    /// Pick an exercise of the given categories, or of any category when there is no such exercise.
+    /// The exercises shown recently are skipped while there are others to choose from.
+    pub fn pick<'a>(
+        &mut self,
+        exercises: &'a [Exercise],
+        categories: &[Category],
+    ) -> Option<&'a Exercise> {
+        let enabled: Vec<&Exercise> = exercises.iter().filter(|e| e.weight != Some(0)).collect();
+        let in_categories: Vec<&Exercise> = enabled
+            .iter()
+            .copied()
+            .filter(|e| e.category.is_some_and(|c| categories.contains(&c)))
+            .collect();
+        let candidates = if in_categories.is_empty() {
+            enabled
+        } else {
+            in_categories
+        };
+
+        // always leave one candidate
+        let memory = RECENT_EXERCISES.min(candidates.len().saturating_sub(1));
+        let avoided: Vec<&String> = self
+            .recent
+            .iter()
+            .rev()
+            .filter(|text| candidates.iter().any(|e| &&e.text == text))
+            .take(memory)
+            .collect();
+        let fresh: Vec<&Exercise> = candidates
+            .iter()
+            .copied()
+            .filter(|e| !avoided.contains(&&e.text))
+            .collect();
+
+        let weight = |e: &&Exercise| e.weight.unwrap_or(1);
+        let exercise = *fresh.choose_weighted(&mut self.rng, weight).ok()?;
+        self.remember(&exercise.text);
+
+        Some(exercise)
+    }
+
+    fn remember(&mut self, text: &str) {
+        self.recent.retain(|recent| recent != text);
+        self.recent.push_back(text.to_string());
+        while self.recent.len() > RECENT_EXERCISES {
+            self.recent.pop_front();
+        }
+
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        let saved = serde_json::to_string(&self.recent)
+            .map_err(|e| e.into())
+            .and_then(|content| write_atomic(path, content.as_bytes()));
+        if let Err(e) = saved {
+            eprintln!("Can't save the recent exercises: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(text: &str, category: Category, weight: Option<u32>) -> Exercise {
+        Exercise {
+            text: text.to_string(),
+            url: None,
+            icon: None,
+            duration: None,
+            category: Some(category),
+            tags: Vec::new(),
+            weight,
+        }
+    }
+
+    fn catalog() -> Vec<Exercise> {
+        vec![
+            exercise("blink", Category::Eyes, None),
+            exercise("look far", Category::Eyes, None),
+            exercise("roll eyes", Category::Eyes, None),
+            exercise("squats", Category::Stretching, Some(3)),
+            exercise("shoulders", Category::Stretching, None),
+            exercise("box breathing", Category::Breathing, None),
+            exercise("disabled", Category::Mental, Some(0)),
+        ]
+    }
+
+    fn picks(selector: &mut Selector, categories: &[Category], count: usize) -> Vec<String> {
+        let exercises = catalog();
+        (0..count)
+            .map(|_| selector.pick(&exercises, categories).unwrap().text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_repeat_until_every_exercise_was_shown() {
+        let mut selector = Selector::seeded(42);
+        let shown = picks(&mut selector, &[], 60);
+
+        // 6 enabled exercises, the last 5 shown are avoided
+        for window in shown.windows(6) {
+            let mut texts = window.to_vec();
+            texts.sort();
+            texts.dedup();
+            assert_eq!(texts.len(), 6, "{window:?}");
+        }
+        assert!(!shown.contains(&"disabled".to_string()));
+    }
+
+    #[test]
+    fn test_same_seed_same_picks() {
+        assert_eq!(
+            picks(&mut Selector::seeded(7), &[], 20),
+            picks(&mut Selector::seeded(7), &[], 20)
+        );
+    }
+
+    #[test]
+    fn test_pick_in_categories() {
+        let mut selector = Selector::seeded(1);
+        let shown = picks(&mut selector, &[Category::Eyes], 9);
+        assert!(shown
+            .iter()
+            .all(|text| ["blink", "look far", "roll eyes"].contains(&text.as_str())));
+        // a single exercise in the category is repeated
+        assert_eq!(
+            picks(&mut selector, &[Category::Breathing], 3),
+            vec!["box breathing"; 3]
+        );
+        // no exercise in the category, any one is picked
+        assert_eq!(picks(&mut selector, &[Category::Mental], 1).len(), 1);
+    }
+
+    #[test]
+    fn test_weights() {
+        let exercises = vec![
+            exercise("light", Category::Stretching, Some(1)),
+            exercise("heavy", Category::Stretching, Some(9)),
+        ];
+        let mut selector = Selector::seeded(3);
+        // forgetting the recent ones, the picks follow the 9:1 weights
+        let mut heavy = 0;
+        for _ in 0..1000 {
+            selector.recent.clear();
+            if selector.pick(&exercises, &[]).unwrap().text == "heavy" {
+                heavy += 1;
+            }
+        }
+        assert!((850..=950).contains(&heavy), "{heavy}");
+    }
+
+    #[test]
+    fn test_recent_exercises_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("recent_exercises.json");
+
+        let mut selector = Selector::persistent(&path);
+        let first = picks(&mut selector, &[], 5);
+
+        let mut selector = Selector::persistent(&path);
+        assert_eq!(Vec::from(selector.recent.clone()), first);
+        let next = picks(&mut selector, &[], 1);
+        assert!(!first.contains(&next[0]));
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::{Read, Write};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::{self, SendError};
+use std::sync::Arc;
+
+/// Channel whose receiving end can be polled, to wake the main loop from the threads
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    let wake = UnixStream::pair()
+        .and_then(|(writer, reader)| {
+            writer.set_nonblocking(true)?;
+            reader.set_nonblocking(true)?;
+            Ok((writer, reader))
+        })
+        .map_err(|e| {
+            eprintln!("Can't create a wake up socket, the events wait for the next tick: {e}")
+        })
+        .ok();
+    let (writer, reader) = wake.unzip();
+    let writer = writer.map(Arc::new);
+
+    (
+        Sender {
+            sender,
+            wake: writer.clone(),
+        },
+        Receiver {
+            receiver,
+            wake: reader,
+            _writer: writer,
+        },
+    )
+}
+
+/// Sending end, a byte is written on the wake up socket after each value
+#[derive(Debug)]
+pub struct Sender<T> {
+    sender: mpsc::Sender<T>,
+    wake: Option<Arc<UnixStream>>,
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            sender: self.sender.clone(),
+            wake: self.wake.clone(),
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.sender.send(value)?;
+        if let Some(wake) = &self.wake {
+            // a full socket is readable already
+            let _ = (&**wake).write(&[1]);
+        }
+        Ok(())
+    }
+}
+
+/// Receiving end, readable when values are pending
+#[derive(Debug)]
+pub struct Receiver<T> {
+    receiver: mpsc::Receiver<T>,
+    wake: Option<UnixStream>,
+    /// Keeps the socket open once the senders are gone, it would be readable forever
+    _writer: Option<Arc<UnixStream>>,
+}
+
+impl<T> Receiver<T> {
+    /// Values received so far, without blocking
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, T> {
+        if let Some(mut wake) = self.wake.as_ref() {
+            let mut buf = [0; 64];
+            while matches!(wake.read(&mut buf), Ok(n) if n > 0) {}
+        }
+        self.receiver.try_iter()
+    }
+
+    /// File descriptor to poll
+    pub fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.wake.as_ref().map(|wake| wake.as_fd())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustix::event::{poll, PollFd, PollFlags, Timespec};
+    use std::thread;
+
+    fn readable(receiver: &Receiver<u32>) -> bool {
+        let mut fds = [PollFd::from_borrowed_fd(
+            receiver.fd().unwrap(),
+            PollFlags::IN,
+        )];
+        poll(&mut fds, Some(&Timespec::default())).unwrap() > 0
+    }
+
+    #[test]
+    fn test_values_wake_the_receiver() {
+        let (sender, receiver) = channel();
+        assert!(!readable(&receiver));
+
+        thread::spawn(move || {
+            sender.send(1).unwrap();
+            sender.send(2).unwrap();
+        })
+        .join()
+        .unwrap();
+        assert!(readable(&receiver));
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+        // the sender is gone with the thread, nothing more to read
+        assert!(!readable(&receiver));
+    }
+}
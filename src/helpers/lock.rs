@@ -0,0 +1,58 @@
+use rustix::fs::{flock, FlockOperation};
+use rustix::io::Errno;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::helpers::state_dir;
+
+/// Lock of the instance writing the history, the checkpoint and the metrics,
+/// waybar starts one instance per output
+#[derive(Debug)]
+pub struct InstanceLock {
+    /// Locked as long as it is open
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Take the lock, none when another instance holds it
+    pub fn acquire(path: &Path) -> io::Result<Option<InstanceLock>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        match flock(&file, FlockOperation::NonBlockingLockExclusive) {
+            Ok(()) => Ok(Some(InstanceLock { _file: file })),
+            Err(Errno::WOULDBLOCK) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Lock file in the state directory
+pub fn default_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("instance.lock"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_holder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("instance.lock");
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert!(lock.is_some());
+        assert!(InstanceLock::acquire(&path).unwrap().is_none());
+
+        // released with the holder
+        drop(lock);
+        assert!(InstanceLock::acquire(&path).unwrap().is_some());
+    }
+}
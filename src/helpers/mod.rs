@@ -1,128 +1,153 @@
-use rand::seq::SliceRandom;
-
-pub fn get_random_gymnastic() -> (String, Option<String>) {
-    // Resources:
-    // * https://github.com/slgobinath/SafeEyes/blob/master/safeeyes/config/safeeyes.json#L20
-    // * ChatGPT, internet ...
-    let data: Vec<(String, Option<String>)> = vec![
-        ("Take a brief walk and hydrate with water 💧", None),
-        ("Blink your eyes 😉", None),
-        ("Close your eyes and envision a peaceful place 🏞️", None),
-        (
-            "Connect with a loved one through a phone call or text message 📱",
-            None,
-        ),
-        (
-            "Create a gratitude list to appreciate your blessings 📝",
-            None,
-        ),
-        ("Do a cardiac coherence session ❤️‍🩹", None),
-        (
-            "Engage in a creative activity, such as drawing or painting 🎨",
-            None,
-        ),
-        (
-            "Feel the texture of an object in your hands, grounding you 🖐️",
-            None,
-        ),
-        ("Focus on a point in the far distance 🌅", None),
-        (
-            "Focus on the present moment, letting go of worries 🌈",
-            None,
-        ),
-        (
-            "Gently apply a soothing hand cream for soft, relaxed skin 🤲",
-            None,
-        ),
-        ("Gently stretch your muscles to release tension 🧘", None),
-        (
-            "Give yourself a gentle facial massage to release tension 😊",
-            None,
-        ),
-        ("Imagine waves gently crashing on a serene beach 🌊", None),
-        (
-            "Inhale for four counts, hold for four, exhale for four 🌬",
-            Some("https://google.com".to_string()),
-        ),
-        (
-            "Inhale the aroma of lavender to soothe your nerves 🌿",
-            None,
-        ),
-        (
-            "Laugh out loud by watching a funny video or reading jokes 😂",
-            None,
-        ),
-        ("Lean back at your seat and relax 💺", None),
-        (
-            "Light a scented candle to create a soothing ambiance 🕯️",
-            None,
-        ),
-        ("Listen to an inspiring podcast or audiobook 🎧", None),
-        ("Listen to calming music or nature sounds 🎶", None),
-        ("Meditate for a few minutes to clear your mind 🧘", None),
-        (
-            "Perform a random act of kindness to boost your mood 💕",
-            None,
-        ),
-        (
-            "Perform gentle yoga poses to increase flexibility and balance 🧘🏼",
-            None,
-        ),
-        (
-            "Perform hand and arm stretches to release muscle tension 💪",
-            None,
-        ),
-        ("Picture your worries as balloons, floating away 🎈", None),
-        ("Plant or tend to a garden, connecting with nature 🌱", None),
-        (
-            "Practice mindfulness by focusing on the present moment 🌸",
-            None,
-        ),
-        (
-            "Practice progressive muscle relaxation, tensing and releasing muscles 👣",
-            None,
-        ),
-        ("Read a few pages of a comforting book 📖", None),
-        ("Repeat a calming mantra, such as 'I am at peace.' ☮️", None),
-        ("Roll your eyes a few times to each side 👀", None),
-        ("Rotate your eyes in clockwise direction 🔄", None),
-        ("Rotate your eyes in counterclockwise direction 🔄", None),
-        ("Savor a delicious, healthy snack 🍇", None),
-        ("Savor a warm cup of tea or a soothing scent ☕", None),
-        ("Sip a warm, calming beverage, like herbal tea ☕", None),
-        (
-            "Slowly roll your shoulders back and release tension 🦺",
-            None,
-        ),
-        ("Soak up the sun and get a dose of Vitamin D ☀", None),
-        ("Stretch your legs 🚶", None),
-        ("Take a deep breath and let it out slowly 🌬️", None),
-        (
-            "Take a nap or rest, allowing your body to rejuvenate 💤",
-            None,
-        ),
-        ("Take a relaxing bath with bubbles or bath salts 🛀", None),
-        (
-            "Take slow, deep breaths and focus on your breathing 🌬",
-            None,
-        ),
-        ("Tightly close your eyes 😌", None),
-        ("Visualize your stress melting away like ice ❄", None),
-        ("Walk for a while 🚶", None),
-        ("Whisper to yourself, 'I am calm and in control' 😊", None),
-        (
-            "Wrap yourself in a cozy blanket and relax on the couch 🛋️",
-            None,
-        ),
-        (
-            "Write in a journal to express your thoughts and emotions 🖋",
-            None,
-        ),
-    ]
-    .iter()
-    .cloned()
-    .map(|(s, x)| (s.trim().to_string(), x))
-    .collect();
-
-    data.choose(&mut rand::thread_rng()).unwrap().clone()
+pub mod channel;
+pub mod lock;
+#[cfg(test)]
+pub mod private_bus;
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// State directory, `$XDG_STATE_HOME/burnout-detector`
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+}
+
+/// Write a file atomically, a crash leaves the previous content
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+
+    fs::rename(tmp, path)
+}
+
+/// Time without input before the idle sources report the user as idle
+pub fn idle_detection_timeout(idle_timeout: u64) -> Duration {
+    if idle_timeout < 60 {
+        // if idle_timeout less than 60s,
+        // make the detection 1s less
+        Duration::from_secs(idle_timeout.saturating_sub(1))
+    } else {
+        // or use 60s as timeout detection
+        Duration::from_secs(60)
+    }
+}
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Waybar clock, `hh:mm`, showing the seconds would need a refresh every second
+pub fn hhmm(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Countdown rounded up to the minute, it shows `00:00` only once over
+pub fn round_up_minute(remaining: Duration) -> Duration {
+    let minutes = remaining.as_millis().div_ceil(MINUTE.as_millis());
+    Duration::from_secs(u64::try_from(minutes).unwrap_or(u64::MAX / 60) * 60)
+}
+
+/// Time before a clock shown to the minute changes, `elapsed` growing
+pub fn next_minute(elapsed: Duration) -> Duration {
+    MINUTE - Duration::from_millis((elapsed.as_millis() % MINUTE.as_millis()) as u64)
+}
+
+/// Time before a countdown rounded up to the minute changes
+pub fn next_countdown_minute(remaining: Duration) -> Duration {
+    (remaining + MINUTE).saturating_sub(round_up_minute(remaining))
+}
+
+/// Open an URL with the desktop default application
+pub fn open_url(url: &str) {
+    let url = url.to_string();
+    thread::spawn(move || {
+        if let Err(e) = Command::new("xdg-open").arg(&url).status() {
+            eprintln!("Can't open {url}: {e}");
+        }
+    });
+}
+
+/// Parse a duration like `90s`, `15m`, `1h30m`, a bare number is in seconds
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("empty duration".into());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            _ => return Err(format!("invalid duration '{s}', use s, m or h units")),
+        };
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{s}', a number is missing"))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(|| format!("duration '{s}' is too long"))?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(format!("invalid duration '{s}', the unit is missing"));
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minute_clocks() {
+        assert_eq!(hhmm(Duration::from_secs(3 * 3600 + 5 * 60 + 59)), "03:05");
+        assert_eq!(
+            round_up_minute(Duration::from_millis(60_500)),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            next_minute(Duration::from_millis(90_500)),
+            Duration::from_millis(29_500)
+        );
+        assert_eq!(
+            next_countdown_minute(Duration::from_millis(60_500)),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            next_countdown_minute(Duration::from_secs(120)),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration(" 1h30m "), Ok(Duration::from_secs(5400)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("2d").is_err());
+    }
 }
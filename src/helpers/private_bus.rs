@@ -0,0 +1,42 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+
+/// Private D-Bus daemon, killed on drop
+pub struct PrivateBus {
+    child: Child,
+    /// Address to connect to
+    pub address: String,
+}
+
+impl PrivateBus {
+    pub fn start() -> PrivateBus {
+        let mut child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("dbus-daemon is required to run this test");
+        let mut address = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut address)
+            .expect("Can't read the dbus-daemon address");
+
+        PrivateBus {
+            child,
+            address: address.trim().to_string(),
+        }
+    }
+
+    pub fn connect(&self) -> zbus::blocking::Connection {
+        zbus::blocking::ConnectionBuilder::address(self.address.as_str())
+            .and_then(|builder| builder.build())
+            .expect("Can't connect to the private bus")
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use crate::helpers::{state_dir, write_atomic};
+use crate::history::SessionKind;
+
+/// Current session, saved to survive a restart
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Active, idle or break
+    pub kind: SessionKind,
+    /// Session start
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    /// When the checkpoint was written
+    #[serde(with = "time::serde::rfc3339")]
+    pub saved_at: OffsetDateTime,
+    /// Number of notifications sent
+    pub counter_sent: u64,
+    /// Next notification time
+    pub next_send_time: Duration,
+    /// Delay between the notifications
+    pub notification_delay: Duration,
+}
+
+/// Checkpoint in `$XDG_STATE_HOME/burnout-detector`
+pub fn default_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("checkpoint.json"))
+}
+
+impl Checkpoint {
+    /// Write the checkpoint atomically, a crash leaves the previous one
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_atomic(path, serde_json::to_string(self)?.as_bytes())
+    }
+
+    /// Read the checkpoint, if there is a valid one
+    pub fn load(path: &Path) -> Option<Checkpoint> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("checkpoint.json");
+        assert_eq!(Checkpoint::load(&path), None);
+
+        let checkpoint = Checkpoint {
+            kind: SessionKind::Active,
+            start: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+            saved_at: OffsetDateTime::from_unix_timestamp(1_700_003_600).unwrap(),
+            counter_sent: 2,
+            next_send_time: Duration::from_millis(397500),
+            notification_delay: Duration::from_secs(45),
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path), Some(checkpoint));
+
+        fs::write(&path, "{\"kind\":").unwrap();
+        assert_eq!(Checkpoint::load(&path), None);
+    }
+}
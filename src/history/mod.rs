@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+pub mod checkpoint;
+
+use crate::helpers::state_dir;
+
+/// History file name, the rotated ones get a `.1`, `.2`... suffix
+const HISTORY_FILE: &str = "history.jsonl";
+/// Rotate the history file above this size
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Number of rotated files kept
+const ROTATED_FILES: usize = 5;
+
+/// Kind of recorded session
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    Active,
+    Idle,
+    Break,
+}
+
+/// A finished session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Session start
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    /// Session end
+    #[serde(with = "time::serde::rfc3339")]
+    pub end: OffsetDateTime,
+    /// Session length in seconds
+    pub duration_secs: u64,
+    /// Active, idle or break
+    pub kind: SessionKind,
+    /// Number of notifications sent during the session
+    pub notifications: u64,
+}
+
+/// Append only log of the sessions, one JSON record per line
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Directory holding the history files
+    dir: PathBuf,
+    /// Rotate the current file above this size
+    max_size: u64,
+    /// Another instance writes the history, the records are dropped
+    pub read_only: bool,
+}
+
+impl History {
+    pub fn new(dir: &Path) -> History {
+        History {
+            dir: dir.to_path_buf(),
+            max_size: MAX_FILE_SIZE,
+            read_only: false,
+        }
+    }
+
+    /// History in `$XDG_STATE_HOME/burnout-detector`
+    pub fn open_default() -> Option<History> {
+        state_dir().map(|dir| History::new(&dir))
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(HISTORY_FILE),
+            n => self.dir.join(format!("{HISTORY_FILE}.{n}")),
+        }
+    }
+
+    /// Append a record, the line is written at once and synced to the disk
+    pub fn append(&self, record: &Record) -> io::Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        self.rotate()?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(self.path(0))?;
+        // a crash could have left a truncated line, don't glue the new record to it
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        file.sync_data()
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        match fs::metadata(self.path(0)) {
+            Ok(metadata) if metadata.len() >= self.max_size => {}
+            _ => return Ok(()),
+        }
+
+        for index in (1..ROTATED_FILES).rev() {
+            if self.path(index).exists() {
+                fs::rename(self.path(index), self.path(index + 1))?;
+            }
+        }
+        fs::rename(self.path(0), self.path(1))
+    }
+
+    /// Every readable record, oldest first, the corrupted lines are skipped
+    pub fn read_all(&self) -> io::Result<Vec<Record>> {
+        let mut records = Vec::new();
+        for index in (0..=ROTATED_FILES).rev() {
+            let file = match File::open(self.path(index)) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for line in BufReader::new(file).lines() {
+                if let Ok(record) = serde_json::from_str(&line?) {
+                    records.push(record);
+                }
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn record(minutes: i64, kind: SessionKind) -> Record {
+        let start = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        Record {
+            start,
+            end: start + Duration::minutes(minutes),
+            duration_secs: minutes as u64 * 60,
+            kind,
+            notifications: 0,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(&dir.path().join("state"));
+        assert!(history.read_all().unwrap().is_empty());
+
+        history.append(&record(50, SessionKind::Active)).unwrap();
+        history.append(&record(10, SessionKind::Idle)).unwrap();
+
+        assert_eq!(
+            history.read_all().unwrap(),
+            vec![
+                record(50, SessionKind::Active),
+                record(10, SessionKind::Idle)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncated_line_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path());
+        history.append(&record(50, SessionKind::Active)).unwrap();
+
+        // simulate a crash in the middle of a write
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(history.path(0))
+            .unwrap();
+        file.write_all(b"{\"start\":\"2023-").unwrap();
+
+        history.append(&record(5, SessionKind::Break)).unwrap();
+        assert_eq!(
+            history.read_all().unwrap(),
+            vec![
+                record(50, SessionKind::Active),
+                record(5, SessionKind::Break)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_only_history_is_not_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::new(dir.path());
+        history.read_only = true;
+
+        history.append(&record(1, SessionKind::Active)).unwrap();
+        assert!(history.read_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History {
+            dir: dir.path().to_path_buf(),
+            max_size: 1,
+            read_only: false,
+        };
+
+        for minutes in 1..=(ROTATED_FILES as i64 + 3) {
+            history
+                .append(&record(minutes, SessionKind::Active))
+                .unwrap();
+        }
+
+        assert!(history.path(ROTATED_FILES).exists());
+        assert!(!history.path(ROTATED_FILES + 1).exists());
+        // the oldest records are dropped with the last rotated file
+        let records = history.read_all().unwrap();
+        assert_eq!(records.len(), ROTATED_FILES + 1);
+        assert_eq!(records[0], record(3, SessionKind::Active));
+        assert_eq!(
+            records[ROTATED_FILES],
+            record(ROTATED_FILES as i64 + 3, SessionKind::Active)
+        );
+    }
+}
@@ -1,35 +1,142 @@
 use hhmmss::Hhmmss;
 use notify_rust::{Hint, Notification, Urgency};
+use std::collections::HashMap;
+use std::os::fd::BorrowedFd;
+use std::thread;
 use std::time::Duration;
+use time::OffsetDateTime;
+use zbus::blocking::{Connection, Proxy};
 
-use crate::helpers::*;
+use crate::config::Stacking;
+use crate::exercises::selector::Selector;
+use crate::exercises::Category;
+use crate::helpers::channel::{self, Receiver, Sender};
+use crate::logic::budget::BudgetSettings;
+use crate::logic::overtime::WorkingHours;
+use crate::logic::pomodoro::{Pomodoro, PomodoroEvent};
+use crate::logic::schedule::{Level, ScheduleKind, ScheduleSettings};
 use crate::logic::Status;
+use crate::stats::risk::{Risk, RiskLevel};
 use crate::Args;
 
 const MIN_NOTIFICATION_TIME: Duration = Duration::from_secs(15);
 const MAX_RECOMMANDED_PAUSE_TIME: Duration = Duration::from_secs(600);
+/// Delay added by the snooze notification action
+pub const NOTIFICATION_SNOOZE: Duration = Duration::from_secs(600);
+/// Action key sent by the listener when the notification is closed
+const CLOSED_KEY: &str = "__closed";
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Notification id and action key
+type ActionResponse = (u32, String);
+
+/// What a notification is about, a new one replaces the displayed one of the same kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reminder {
+    Eyes,
+    Schedule(ScheduleKind),
+    Pomodoro,
+    Budget,
+    Overtime,
+    Risk,
+}
+
+/// Notification shown and not closed yet
+#[derive(Debug)]
+struct Displayed {
+    reminder: Reminder,
+    /// Exercise guide opened by its action
+    guide_url: Option<String>,
+}
+
+/// Action clicked in a notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationAction {
+    /// Delay the next notification
+    Snooze,
+    /// The user confirms taking a break
+    TakeBreak,
+    /// Open the exercise guide
+    OpenGuide(String),
+}
+
+impl NotificationAction {
+    /// Parse the action key sent back by the notification server
+    fn from_key(key: &str, url: Option<&str>) -> Option<NotificationAction> {
+        match (key, url) {
+            ("snooze", _) => Some(NotificationAction::Snooze),
+            ("break", _) => Some(NotificationAction::TakeBreak),
+            ("open-guide", Some(url)) => Some(NotificationAction::OpenGuide(url.to_string())),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Alert {
     /// App arguments
     pub args: Args,
-    /// Notification urgency
-    pub urgency: Urgency,
     /// Number of notification sent
     pub counter_sent: u64,
+    /// Notifications sent since the start, reminders included
+    pub total_sent: u64,
     /// Next notification time
     pub next_send_time: Duration,
     /// Active start at this time
     pub notification_delay_secs: Duration,
     /// Don't send real notification in test
     pub quiet: bool,
+    /// Responses sent by the action listener
+    actions: (Sender<ActionResponse>, Receiver<ActionResponse>),
+    /// Displayed notifications by id, updated in place by the next one of the same kind
+    displayed: HashMap<u32, Displayed>,
+    /// Session bus connection of the action listener and of the closed notifications, opened on the first use
+    connection: Option<Connection>,
+    /// Picks the suggested exercises
+    pub selector: Selector,
 }
 
 impl Alert {
+    pub fn new(args: &Args) -> Alert {
+        let next_send_time = if args.waybar {
+            Duration::from_secs(args.idle_timeout * (args.max_active_sessions + 1))
+        } else {
+            Duration::from_secs(0)
+        };
+
+        let notification_delay_secs = if args.waybar {
+            Duration::from_secs(args.idle_timeout * args.max_active_sessions)
+        } else {
+            Duration::from_secs(args.idle_timeout)
+        };
+
+        Alert {
+            args: args.clone(),
+            next_send_time,
+            counter_sent: 0,
+            total_sent: 0,
+            notification_delay_secs,
+            quiet: false,
+            actions: channel::channel(),
+            displayed: HashMap::new(),
+            connection: None,
+            selector: Selector::default(),
+        }
+    }
+
+    /// Rebuild the thresholds from a new configuration, keeping the escalation counter
+    pub fn reload(&mut self, args: &Args) {
+        let alert = Alert::new(args);
+        self.args = alert.args;
+        self.next_send_time = alert.next_send_time;
+        self.notification_delay_secs = alert.notification_delay_secs;
+    }
+
     pub fn send_notification(&mut self, urgency: Urgency, status: &Status) -> bool {
         match status {
             Status::Active(start, elapsed) => self.notification_active(elapsed, start, urgency),
-            Status::Idle(_, _) => false,
+            Status::Idle(_, _) | Status::Break(_, _) => false,
         }
     }
 
@@ -44,21 +151,57 @@ impl Alert {
             return false;
         }
 
-        self.notification_delay_secs = self.notification_delay_secs.max(MIN_NOTIFICATION_TIME);
+        // the escalation only moves on once the notification is shown
+        let mut notification_delay_secs = self.notification_delay_secs.max(MIN_NOTIFICATION_TIME);
 
         let should_harass =
             *elapsed > Duration::from_secs(self.args.max_active_sessions * self.args.idle_timeout);
 
-        if should_harass && self.notification_delay_secs >= MIN_NOTIFICATION_TIME * 2 {
-            self.notification_delay_secs /= 2;
+        if should_harass && notification_delay_secs >= MIN_NOTIFICATION_TIME * 2 {
+            notification_delay_secs /= 2;
         }
 
-        self.next_send_time = *elapsed + self.notification_delay_secs;
-
         let pause_time =
             Duration::from_secs(self.args.idle_timeout / 2).max(MAX_RECOMMANDED_PAUSE_TIME);
 
-        self.counter_sent += 1;
+        let counter_sent = self.counter_sent + 1;
+
+        if !self.quiet {
+            let exercise = self
+                .selector
+                .pick(&self.args.exercises, &self.args.exercise_categories);
+            let text = exercise.map(|exercise| exercise.to_string());
+            let url = exercise.and_then(|exercise| exercise.url.clone());
+            let icon = exercise
+                .and_then(|exercise| exercise.icon.as_deref())
+                .unwrap_or("media-playback-pause-symbolic");
+            let mut notification = Notification::new();
+            notification
+                .summary(&format!("Burnout detector ({}x)", counter_sent))
+                .body(&format!(
+                    "You didn't take a break for {}\nYou should take a <b>{}</b> break and do some <b>gym</b> exercice!\n\n<b>{}</b>",
+                    elapsed.hhmmss(),
+                    pause_time.hhmmss(),
+                    text.unwrap_or_default()
+                ))
+                .icon(icon)
+                .appname("burnout_detector")
+                .action("snooze", &format!("Snooze {} min", NOTIFICATION_SNOOZE.as_secs() / 60))
+                .action("break", "I'm taking a break")
+                .hint(Hint::Urgency(urgency))
+                .timeout(Duration::from_millis(self.args.notification_timeout.into()));
+            if url.is_some() {
+                notification.action("open-guide", "Open guide");
+            }
+            if !self.show(Reminder::Eyes, notification, url) {
+                return false;
+            }
+        }
+
+        self.notification_delay_secs = notification_delay_secs;
+        self.next_send_time = *elapsed + notification_delay_secs;
+        self.counter_sent = counter_sent;
+        self.total_sent += 1;
 
         if self.args.debug {
             eprintln!(
@@ -69,44 +212,360 @@ impl Alert {
             );
         }
 
-        if !self.quiet {
-            return match get_random_gymnastic() {
-                (exercise, Some(url)) => {
-                    Notification::new()
-                            .summary(&format!("Burnout detector ({}x)", self.counter_sent))
-                            .body(&format!(
-                                "You didn't take a break for {}\nYou should take a <b>{}</b> break and do some <b>gym</b> exercice!\n\n<b>{}</b>",
-                               elapsed.hhmmss(),
-                               pause_time.hhmmss(),
-                               exercise
-                            ))
-                            .icon("media-playback-pause-symbolic")
-                            .appname("burnout_detector")
-                            .action(&format!("xdg-open {}", &url), "open URL")
-                            .hint(Hint::Urgency(urgency))
-                            .timeout(0)
-                            .show().is_ok()
-                }
-                (exercise, None) => {
-                    Notification::new()
-                        .summary(&format!("Burnout detector ({}x)", self.counter_sent))
-                        .body(&format!(
-                            "You didn't take a break for {}\nYou should take a <b>{}</b> break and do some <b>gym</b> exercice!\n\n<b>{}</b>",
-                           elapsed.hhmmss(),
-                           pause_time.hhmmss(),
-                           exercise
-                        ))
-                        .icon("media-playback-pause-symbolic")
-                        .appname("burnout_detector")
-                        .hint(Hint::Urgency(urgency))
-                        .timeout(0)
-                        .show().is_ok()
-                }
-            };
+        true
+    }
+
+    /// Tell that a scheduled break is due, or overdue
+    pub fn send_schedule_notification(
+        &mut self,
+        settings: &ScheduleSettings,
+        level: Level,
+        elapsed: Duration,
+    ) -> bool {
+        if !settings.notify {
+            return false;
+        }
+        if self.args.debug {
+            eprintln!("A {} is due, {level:?}", settings.kind);
+        }
+        let urgency = match level {
+            Level::Critical => Urgency::Critical,
+            _ => settings.urgency,
+        };
+        self.send_reminder(
+            Reminder::Schedule(settings.kind),
+            &format!("Burnout detector: {}", settings.kind),
+            &format!(
+                "You are working for {} without a {}\nLook away from the screen for <b>{}</b>",
+                elapsed.hhmmss(),
+                settings.kind,
+                settings.duration.hhmmss(),
+            ),
+            urgency,
+            Some(&settings.categories),
+        )
+    }
+
+    /// Tell about a pomodoro phase change
+    pub fn send_pomodoro_notification(
+        &mut self,
+        event: PomodoroEvent,
+        pomodoro: &Pomodoro,
+    ) -> bool {
+        if self.args.debug {
+            eprintln!("Pomodoro {event:?}");
+        }
+        let remaining = pomodoro.remaining().hhmmss();
+        let categories = self.args.exercise_categories.clone();
+        match event {
+            PomodoroEvent::BreakDue(phase) => self.send_reminder(
+                Reminder::Pomodoro,
+                &format!("Burnout detector: {phase}"),
+                &format!("Work interval done, step away for <b>{remaining}</b>"),
+                Urgency::Normal,
+                Some(&categories),
+            ),
+            PomodoroEvent::BreakDone => self.send_reminder(
+                Reminder::Pomodoro,
+                "Burnout detector: back to work",
+                &format!("Break done, next one in {remaining}"),
+                Urgency::Low,
+                None,
+            ),
+            PomodoroEvent::CutShort { extended: true } => self.send_reminder(
+                Reminder::Pomodoro,
+                "Burnout detector: break cut short",
+                &format!("The break is extended, step away for another <b>{remaining}</b>"),
+                Urgency::Normal,
+                None,
+            ),
+            PomodoroEvent::CutShort { extended: false } => self.send_reminder(
+                Reminder::Pomodoro,
+                "Burnout detector: break cut short",
+                &format!("Back to work for {remaining}, try to take the whole next break"),
+                Urgency::Low,
+                None,
+            ),
+        }
+    }
+
+    /// Tell that the daily budget is almost used, or exceeded
+    pub fn send_budget_notification(
+        &mut self,
+        settings: &BudgetSettings,
+        level: Level,
+        used: Duration,
+    ) -> bool {
+        if !settings.notify {
+            return false;
+        }
+        if self.args.debug {
+            eprintln!("Daily budget {level:?}, {} used", used.hhmmss());
+        }
+        match level {
+            Level::Ok => false,
+            Level::Warning => self.send_reminder(
+                Reminder::Budget,
+                "Burnout detector: daily budget",
+                &format!(
+                    "You were active for {} today, out of a <b>{}</b> budget\nThink about wrapping up",
+                    used.hhmmss(),
+                    settings.daily.hhmmss()
+                ),
+                Urgency::Normal,
+                None,
+            ),
+            Level::Critical => self.send_reminder(
+                Reminder::Budget,
+                "Burnout detector: daily budget exceeded",
+                &format!(
+                    "You were active for {} today, over the <b>{}</b> budget\nTime to call it a day",
+                    used.hhmmss(),
+                    settings.daily.hhmmss()
+                ),
+                Urgency::Critical,
+                None,
+            ),
+        }
+    }
+
+    /// Tell that the user keeps working after hours
+    pub fn send_overtime_notification(
+        &mut self,
+        hours: &WorkingHours,
+        local: OffsetDateTime,
+        overtime: Duration,
+    ) -> bool {
+        if !hours.notify {
+            return false;
+        }
+        if self.args.debug {
+            eprintln!("Overtime for {}", overtime.hhmmss());
+        }
+        self.send_reminder(
+            Reminder::Overtime,
+            "Burnout detector: after hours",
+            &format!(
+                "It's {:02}:{:02} and you've been active for {} min\nThe working day is over, time to rest",
+                local.hour(),
+                local.minute(),
+                overtime.as_secs() / 60
+            ),
+            Urgency::Normal,
+            None,
+        )
+    }
+
+    /// Weekly summary of the burnout risk
+    pub fn send_risk_summary(&mut self, risk: &Risk) -> bool {
+        if self.args.debug {
+            eprintln!("Weekly summary, {}", risk.describe());
         }
+        let hms = |secs: u64| Duration::from_secs(secs).hhmmss();
+        self.send_reminder(
+            Reminder::Risk,
+            "Burnout detector: weekly summary",
+            &format!(
+                "Burnout risk <b>{}/100</b> ({})\nActive {} the last 7 days, {} the weeks before\n{} break(s) skipped, {} late at night, {} on the weekend\n{} day(s) in a row without rest",
+                risk.score,
+                risk.level,
+                hms(risk.weekly_active_secs),
+                hms(risk.previous_weekly_active_secs),
+                risk.skipped_breaks,
+                hms(risk.late_night_secs),
+                hms(risk.weekend_secs),
+                risk.streak_days
+            ),
+            if risk.level == RiskLevel::High {
+                Urgency::Critical
+            } else {
+                Urgency::Normal
+            },
+            None,
+        )
+    }
+
+    /// Notification replacing the previous one of the same `reminder`, with an exercise of `categories` when given
+    fn send_reminder(
+        &mut self,
+        reminder: Reminder,
+        summary: &str,
+        body: &str,
+        urgency: Urgency,
+        categories: Option<&[Category]>,
+    ) -> bool {
+        if self.args.no_notify {
+            return false;
+        }
+        if self.quiet {
+            self.total_sent += 1;
+            return true;
+        }
+
+        let exercise =
+            categories.and_then(|categories| self.selector.pick(&self.args.exercises, categories));
+        let body = match exercise {
+            Some(exercise) => format!("{body}\n\n<b>{exercise}</b>"),
+            None => body.to_string(),
+        };
+        let mut notification = Notification::new();
+        notification
+            .summary(summary)
+            .body(&body)
+            .icon(
+                exercise
+                    .and_then(|exercise| exercise.icon.as_deref())
+                    .unwrap_or("media-playback-pause-symbolic"),
+            )
+            .appname("burnout_detector")
+            .hint(Hint::Urgency(urgency))
+            .timeout(Duration::from_millis(self.args.notification_timeout.into()));
+        if !self.show(reminder, notification, None) {
+            return false;
+        }
+        self.total_sent += 1;
         true
     }
 
+    /// Show `notification`, in place of the displayed one of the same `reminder` unless they stack
+    fn show(
+        &mut self,
+        reminder: Reminder,
+        mut notification: Notification,
+        guide_url: Option<String>,
+    ) -> bool {
+        // the closed notifications are forgotten by the listener
+        if let Err(e) = self.connection() {
+            eprintln!("Can't listen to the notification actions: {e}");
+        }
+        let replaces_id = match self.args.stacking {
+            Stacking::Replace => self.displayed_id(reminder),
+            Stacking::Stack => None,
+        };
+        if let Some(id) = replaces_id {
+            notification.id(id);
+        }
+
+        match notification.show() {
+            Ok(handle) => {
+                let displayed = Displayed {
+                    reminder,
+                    guide_url,
+                };
+                self.displayed.insert(handle.id(), displayed);
+                true
+            }
+            Err(e) => {
+                eprintln!("Can't send the notification: {e}");
+                false
+            }
+        }
+    }
+
+    /// Latest displayed notification of `reminder`
+    fn displayed_id(&self, reminder: Reminder) -> Option<u32> {
+        self.displayed
+            .iter()
+            .filter(|(_, displayed)| displayed.reminder == reminder)
+            .map(|(id, _)| *id)
+            .max()
+    }
+
+    /// Don't notify before the session reaches `elapsed`
+    pub fn snooze(&mut self, elapsed: Duration) {
+        self.next_send_time = self.next_send_time.max(elapsed);
+    }
+
+    /// Actions clicked since the last call
+    pub fn pending_actions(&mut self) -> Vec<NotificationAction> {
+        let mut actions = Vec::new();
+        let responses: Vec<_> = self.actions.1.try_iter().collect();
+        for (id, key) in responses {
+            // the notification is gone, the next one will be a new one,
+            // and the notifications of the other applications are ignored
+            let Some(displayed) = self.displayed.remove(&id) else {
+                continue;
+            };
+            if key == CLOSED_KEY {
+                continue;
+            }
+            actions.extend(NotificationAction::from_key(
+                &key,
+                displayed.guide_url.as_deref(),
+            ));
+        }
+
+        actions
+    }
+
+    /// Session bus connection, the first one starts the listener of the actions,
+    /// a single thread for all the notifications
+    fn connection(&mut self) -> zbus::Result<Connection> {
+        if let Some(connection) = &self.connection {
+            return Ok(connection.clone());
+        }
+        let connection = Connection::session()?;
+        let signals = Proxy::new(
+            &connection,
+            NOTIFICATIONS_NAME,
+            NOTIFICATIONS_PATH,
+            NOTIFICATIONS_NAME,
+        )?
+        .receive_all_signals()?;
+        let sender = self.actions.0.clone();
+        thread::spawn(move || {
+            for signal in signals {
+                let response = match signal.member().as_ref().map(|member| member.as_str()) {
+                    Some("ActionInvoked") => signal.body::<(u32, String)>().ok(),
+                    Some("NotificationClosed") => signal
+                        .body::<(u32, u32)>()
+                        .ok()
+                        .map(|(id, _)| (id, CLOSED_KEY.to_string())),
+                    _ => None,
+                };
+                if let Some(response) = response {
+                    if sender.send(response).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        self.connection = Some(connection.clone());
+
+        Ok(connection)
+    }
+
+    /// Readable when an action was clicked
+    pub fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.actions.1.fd()
+    }
+
+    /// Close the displayed notifications, the user is taking the break they ask for,
+    /// the weekly summary stays until dismissed
+    pub fn close_notifications(&mut self) {
+        let ids: Vec<u32> = self
+            .displayed
+            .iter()
+            .filter(|(_, displayed)| displayed.reminder != Reminder::Risk)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            self.displayed.remove(&id);
+            let closed = self.connection().and_then(|conn| {
+                conn.call_method(
+                    Some(NOTIFICATIONS_NAME),
+                    NOTIFICATIONS_PATH,
+                    Some(NOTIFICATIONS_NAME),
+                    "CloseNotification",
+                    &(id),
+                )
+            });
+            if let Err(e) = closed {
+                eprintln!("Can't close the notification: {e}");
+            }
+        }
+    }
+
     pub fn reset_notifications(&mut self) {
         self.reset_next_send_time();
 
@@ -124,3 +583,333 @@ impl Alert {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::private_bus::PrivateBus;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::time::Instant;
+    use zbus::zvariant::OwnedValue;
+    use zbus::{dbus_interface, SignalContext};
+
+    /// notify-rust always uses the session bus, one test at a time can replace it
+    static SESSION_BUS: Mutex<()> = Mutex::new(());
+
+    /// Calls received by the fake notification server
+    #[derive(Debug, Default)]
+    struct Calls {
+        /// `replaces_id` and actions of every notification
+        notify: Vec<(u32, Vec<String>)>,
+        /// Closed notifications
+        closed: Vec<u32>,
+    }
+
+    /// Notification server recording the calls
+    #[derive(Default)]
+    struct FakeNotifications {
+        calls: Arc<Mutex<Calls>>,
+        last_id: u32,
+        /// Reject the notifications
+        failing: Arc<AtomicBool>,
+    }
+
+    #[dbus_interface(name = "org.freedesktop.Notifications")]
+    impl FakeNotifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &mut self,
+            _app_name: &str,
+            replaces_id: u32,
+            _app_icon: &str,
+            _summary: &str,
+            _body: &str,
+            actions: Vec<String>,
+            _hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> zbus::fdo::Result<u32> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(zbus::fdo::Error::Failed("no notifications".into()));
+            }
+            self.calls
+                .lock()
+                .unwrap()
+                .notify
+                .push((replaces_id, actions));
+            if replaces_id == 0 {
+                self.last_id += 1;
+                Ok(self.last_id)
+            } else {
+                Ok(replaces_id)
+            }
+        }
+
+        fn close_notification(&self, id: u32) {
+            self.calls.lock().unwrap().closed.push(id);
+        }
+
+        #[dbus_interface(signal)]
+        async fn action_invoked(
+            ctxt: &SignalContext<'_>,
+            id: u32,
+            action_key: &str,
+        ) -> zbus::Result<()>;
+    }
+
+    /// Serve `fake` as the notification server of a private session bus
+    fn serve(
+        fake: FakeNotifications,
+    ) -> (
+        MutexGuard<'static, ()>,
+        PrivateBus,
+        zbus::blocking::Connection,
+    ) {
+        let guard = SESSION_BUS.lock().unwrap_or_else(|e| e.into_inner());
+        let bus = PrivateBus::start();
+        let service = bus.connect();
+        service
+            .object_server()
+            .at(NOTIFICATIONS_PATH, fake)
+            .unwrap();
+        service
+            .request_name("org.freedesktop.Notifications")
+            .unwrap();
+        std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus.address);
+
+        (guard, bus, service)
+    }
+
+    /// Wait a few seconds for the clicked actions
+    fn wait_action(alert: &mut Alert) -> Vec<NotificationAction> {
+        let start = Instant::now();
+        loop {
+            let actions = alert.pending_actions();
+            if !actions.is_empty() || start.elapsed() > Duration::from_secs(5) {
+                return actions;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored alert::`
+    #[test]
+    #[ignore]
+    fn test_notifications_with_a_fake_server() {
+        let fake = FakeNotifications::default();
+        let calls = Arc::clone(&fake.calls);
+        let (_guard, _bus, service) = serve(fake);
+        let server = service
+            .object_server()
+            .interface::<_, FakeNotifications>(NOTIFICATIONS_PATH)
+            .unwrap();
+
+        let args = Args {
+            idle_timeout: 60,
+            ..Default::default()
+        };
+        let mut alert = Alert::new(&args);
+        let start = Instant::now();
+
+        // the second notification updates the first one
+        assert!(alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(60))
+        ));
+        assert!(alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(120))
+        ));
+        assert_eq!(alert.displayed_id(Reminder::Eyes), Some(1));
+
+        // let the waiting thread subscribe
+        thread::sleep(Duration::from_millis(300));
+        zbus::block_on(FakeNotifications::action_invoked(
+            server.signal_context(),
+            1,
+            "snooze",
+        ))
+        .unwrap();
+        assert_eq!(wait_action(&mut alert), vec![NotificationAction::Snooze]);
+        assert_eq!(alert.displayed_id(Reminder::Eyes), None);
+
+        assert!(alert.send_notification(
+            Urgency::Critical,
+            &Status::Active(start, Duration::from_secs(180))
+        ));
+        thread::sleep(Duration::from_millis(300));
+        zbus::block_on(FakeNotifications::action_invoked(
+            server.signal_context(),
+            2,
+            "break",
+        ))
+        .unwrap();
+        assert_eq!(wait_action(&mut alert), vec![NotificationAction::TakeBreak]);
+
+        assert!(alert.send_notification(
+            Urgency::Critical,
+            &Status::Active(start, Duration::from_secs(240))
+        ));
+        alert.close_notifications();
+
+        // the old behaviour, a new popup every time
+        alert.args.stacking = Stacking::Stack;
+        assert!(alert.send_notification(
+            Urgency::Critical,
+            &Status::Active(start, Duration::from_secs(300))
+        ));
+        assert!(alert.send_notification(
+            Urgency::Critical,
+            &Status::Active(start, Duration::from_secs(360))
+        ));
+
+        let calls = calls.lock().unwrap();
+        let replaces: Vec<u32> = calls.notify.iter().map(|(id, _)| *id).collect();
+        assert_eq!(replaces, vec![0, 1, 0, 0, 0, 0]);
+        assert!(calls.notify[0].1.contains(&"snooze".to_string()));
+        assert!(calls.notify[0].1.contains(&"break".to_string()));
+        assert_eq!(calls.closed, vec![3]);
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored alert::`
+    #[test]
+    #[ignore]
+    fn test_failed_notifications_dont_escalate() {
+        let fake = FakeNotifications::default();
+        let failing = Arc::clone(&fake.failing);
+        failing.store(true, Ordering::SeqCst);
+        let (_guard, _bus, _service) = serve(fake);
+
+        let args = Args {
+            idle_timeout: 60,
+            ..Default::default()
+        };
+        let mut alert = Alert::new(&args);
+        let start = Instant::now();
+        let delay = alert.notification_delay_secs;
+
+        assert!(!alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(240))
+        ));
+        assert_eq!(alert.counter_sent, 0);
+        assert_eq!(alert.total_sent, 0);
+        assert_eq!(alert.next_send_time, Duration::ZERO);
+        assert_eq!(alert.notification_delay_secs, delay);
+
+        // the next try goes through
+        failing.store(false, Ordering::SeqCst);
+        assert!(alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(241))
+        ));
+        assert_eq!(alert.counter_sent, 1);
+        assert_eq!(alert.total_sent, 1);
+        assert!(alert.next_send_time > Duration::from_secs(241));
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored alert::`
+    #[test]
+    #[ignore]
+    fn test_guides_of_stacked_notifications() {
+        let (_guard, _bus, service) = serve(FakeNotifications::default());
+        let server = service
+            .object_server()
+            .interface::<_, FakeNotifications>(NOTIFICATIONS_PATH)
+            .unwrap();
+
+        let guide = |text: &str| crate::exercises::Exercise {
+            text: text.to_string(),
+            url: Some(format!("https://example.com/{text}")),
+            icon: None,
+            duration: None,
+            category: None,
+            tags: Vec::new(),
+            weight: None,
+        };
+        let args = Args {
+            idle_timeout: 60,
+            stacking: Stacking::Stack,
+            exercises: vec![guide("squats"), guide("plank")],
+            ..Default::default()
+        };
+        let mut alert = Alert::new(&args);
+        let start = Instant::now();
+
+        assert!(alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(60))
+        ));
+        let first = alert.displayed[&1].guide_url.clone().unwrap();
+        assert!(alert.send_notification(
+            Urgency::Normal,
+            &Status::Active(start, Duration::from_secs(120))
+        ));
+        assert_ne!(alert.displayed[&2].guide_url.as_ref(), Some(&first));
+
+        // the older popup opens its own guide
+        thread::sleep(Duration::from_millis(300));
+        zbus::block_on(FakeNotifications::action_invoked(
+            server.signal_context(),
+            1,
+            "open-guide",
+        ))
+        .unwrap();
+        assert_eq!(
+            wait_action(&mut alert),
+            vec![NotificationAction::OpenGuide(first)]
+        );
+        // the actions of the other applications are ignored
+        zbus::block_on(FakeNotifications::action_invoked(
+            server.signal_context(),
+            42,
+            "break",
+        ))
+        .unwrap();
+        assert_eq!(wait_action(&mut alert), vec![]);
+        assert_eq!(alert.displayed_id(Reminder::Eyes), Some(2));
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored alert::`
+    #[test]
+    #[ignore]
+    fn test_reminders_replace_their_kind() {
+        let fake = FakeNotifications::default();
+        let calls = Arc::clone(&fake.calls);
+        let (_guard, _bus, _service) = serve(fake);
+
+        let mut alert = Alert::new(&Args::default());
+        let micro = ScheduleSettings::new(ScheduleKind::Micro, &Default::default());
+        let budget = BudgetSettings::new(&Default::default());
+        let risk = Risk {
+            score: 10,
+            level: RiskLevel::Low,
+            weekly_active_secs: 0,
+            previous_weekly_active_secs: 0,
+            skipped_breaks: 0,
+            late_night_secs: 0,
+            weekend_secs: 0,
+            streak_days: 0,
+        };
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+
+        assert!(alert.send_schedule_notification(&micro, Level::Warning, minutes(20)));
+        assert!(alert.send_budget_notification(&budget, Level::Warning, minutes(440)));
+        assert!(alert.send_risk_summary(&risk));
+        assert!(alert.send_schedule_notification(&micro, Level::Critical, minutes(30)));
+        assert_eq!(alert.displayed.len(), 3);
+
+        // taking the break closes all but the weekly summary
+        alert.close_notifications();
+        assert_eq!(alert.displayed_id(Reminder::Risk), Some(3));
+        assert!(alert.send_budget_notification(&budget, Level::Critical, minutes(480)));
+
+        let calls = calls.lock().unwrap();
+        let replaces: Vec<u32> = calls.notify.iter().map(|(id, _)| *id).collect();
+        assert_eq!(replaces, vec![0, 0, 0, 1, 0]);
+        let mut closed = calls.closed.clone();
+        closed.sort();
+        assert_eq!(closed, vec![1, 2]);
+    }
+}
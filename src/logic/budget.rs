@@ -0,0 +1,259 @@
+use std::time::Duration;
+use time::{Date, OffsetDateTime, Time, UtcOffset};
+
+use crate::config::BudgetConfig;
+use crate::helpers::hhmm;
+use crate::history::Record;
+use crate::logic::schedule::Level;
+use crate::stats::{Period, Report};
+
+/// Budget settings, the config values completed with the defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetSettings {
+    /// Active time allowed per day
+    pub daily: Duration,
+    /// Local time when a new day starts
+    pub day_start: Time,
+    /// Warn once this much of the budget is used
+    pub warning: Duration,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: bool,
+}
+
+impl BudgetSettings {
+    pub fn new(config: &BudgetConfig) -> BudgetSettings {
+        let daily = Duration::from_secs(config.daily.unwrap_or(8 * 3600));
+        BudgetSettings {
+            daily,
+            day_start: config.day_start.unwrap_or(Time::MIDNIGHT),
+            // saturated, a warning above the budget is rejected
+            warning: daily.saturating_mul(config.warning.unwrap_or(90)) / 100,
+            notify: config.notify.unwrap_or(true),
+        }
+    }
+}
+
+/// Cumulative active time of the day
+#[derive(Debug, Clone)]
+pub struct Budget {
+    pub settings: BudgetSettings,
+    /// Local offset of the day boundary
+    offset: UtcOffset,
+    /// Current day, the date its `day_start` falls on
+    day: Date,
+    /// Active time of the finished sessions of the day
+    spent: Duration,
+    /// Highest level notified today
+    notified: Level,
+}
+
+impl Budget {
+    pub fn new(settings: BudgetSettings, offset: UtcOffset, now: OffsetDateTime) -> Budget {
+        let mut budget = Budget {
+            settings,
+            offset,
+            day: now.date(),
+            spent: Duration::ZERO,
+            notified: Level::Ok,
+        };
+        budget.day = budget.day_of(now);
+        budget
+    }
+
+    /// Day containing `now`, which starts at `day_start`
+    fn day_of(&self, now: OffsetDateTime) -> Date {
+        let since_midnight = self.settings.day_start - Time::MIDNIGHT;
+        (now.to_offset(self.offset) - since_midnight).date()
+    }
+
+    /// Start of the current day
+    fn day_start(&self) -> OffsetDateTime {
+        self.day
+            .with_time(self.settings.day_start)
+            .assume_offset(self.offset)
+    }
+
+    /// Start a new day when `now` is past the boundary
+    fn roll(&mut self, now: OffsetDateTime) {
+        let day = self.day_of(now);
+        if day != self.day {
+            self.day = day;
+            self.spent = Duration::ZERO;
+            self.notified = Level::Ok;
+        }
+    }
+
+    /// Active time of the day in `[start, end]`
+    fn overlap(&self, start: OffsetDateTime, end: OffsetDateTime) -> Duration {
+        Duration::try_from(end - start.max(self.day_start())).unwrap_or_default()
+    }
+
+    /// Count the active sessions of the day from the history
+    pub fn restore(&mut self, records: &[Record], now: OffsetDateTime) {
+        self.roll(now);
+        let report = Report::compute(records, Period::Day, self.day_start(), now, Duration::ZERO);
+        self.spent = Duration::from_secs(report.active_secs);
+    }
+
+    /// An active session ended
+    pub fn add(&mut self, start: OffsetDateTime, end: OffsetDateTime) {
+        self.roll(end);
+        self.spent += self.overlap(start, end);
+    }
+
+    /// Active time of the day, with the current session started at `active_since`
+    pub fn used(&mut self, now: OffsetDateTime, active_since: Option<OffsetDateTime>) -> Duration {
+        self.roll(now);
+        self.peek(now, active_since)
+    }
+
+    /// Same as `used`, without starting a new day
+    pub fn peek(&self, now: OffsetDateTime, active_since: Option<OffsetDateTime>) -> Duration {
+        let spent = if self.day_of(now) == self.day {
+            self.spent
+        } else {
+            Duration::ZERO
+        };
+        spent
+            + active_since
+                .map(|start| self.overlap(start, now))
+                .unwrap_or_default()
+    }
+
+    /// Active time before the level changes
+    pub fn next_change(&self, used: Duration) -> Option<Duration> {
+        [self.settings.warning, self.settings.daily]
+            .into_iter()
+            .find(|threshold| *threshold > used)
+            .map(|threshold| threshold - used)
+    }
+
+    /// Warning when approaching the budget, critical when exceeded
+    pub fn level(&self, used: Duration) -> Level {
+        if used >= self.settings.daily {
+            Level::Critical
+        } else if used >= self.settings.warning {
+            Level::Warning
+        } else {
+            Level::Ok
+        }
+    }
+
+    /// Level to notify, once a day each
+    pub fn next_notification(&mut self, used: Duration) -> Option<Level> {
+        let level = self.level(used);
+        if level <= self.notified {
+            return None;
+        }
+        self.notified = level;
+
+        Some(level)
+    }
+
+    /// Tooltip line
+    pub fn describe(&self, used: Duration) -> String {
+        match used.checked_sub(self.settings.daily) {
+            Some(over) if over.as_secs() > 0 => format!(
+                "Active {} today, {} over the daily budget",
+                hhmm(used),
+                hhmm(over)
+            ),
+            _ => format!(
+                "Active {} today, out of {}",
+                hhmm(used),
+                hhmm(self.settings.daily)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::SessionKind;
+    use time::macros::{datetime, time};
+
+    fn budget(now: OffsetDateTime) -> Budget {
+        let config = BudgetConfig {
+            daily: Some(4 * 3600),
+            day_start: Some(time!(04:00)),
+            ..BudgetConfig::default()
+        };
+        Budget::new(BudgetSettings::new(&config), UtcOffset::UTC, now)
+    }
+
+    fn record(start: OffsetDateTime, end: OffsetDateTime, kind: SessionKind) -> Record {
+        Record {
+            start,
+            end,
+            duration_secs: (end - start).whole_seconds() as u64,
+            kind,
+            notifications: 0,
+        }
+    }
+
+    #[test]
+    fn test_day_boundary() {
+        let now = datetime!(2026-03-10 02:00 UTC);
+        let mut budget = budget(now);
+        // before 4:00, still the previous day
+        assert_eq!(budget.day, time::macros::date!(2026 - 03 - 09));
+
+        budget.restore(
+            &[
+                record(
+                    datetime!(2026-03-09 03:00 UTC),
+                    datetime!(2026-03-09 05:00 UTC),
+                    SessionKind::Active,
+                ),
+                record(
+                    datetime!(2026-03-09 22:00 UTC),
+                    datetime!(2026-03-10 01:30 UTC),
+                    SessionKind::Active,
+                ),
+                record(
+                    datetime!(2026-03-10 01:30 UTC),
+                    datetime!(2026-03-10 01:45 UTC),
+                    SessionKind::Idle,
+                ),
+            ],
+            now,
+        );
+        let used = budget.used(now, Some(datetime!(2026-03-10 01:45 UTC)));
+        assert_eq!(used, Duration::from_secs((60 + 210 + 15) * 60));
+        assert_eq!(budget.level(used), Level::Critical);
+        assert_eq!(
+            budget.describe(used),
+            "Active 04:45 today, 00:45 over the daily budget"
+        );
+
+        // a session across the boundary only counts from 4:00
+        budget.add(
+            datetime!(2026-03-10 03:00 UTC),
+            datetime!(2026-03-10 04:30 UTC),
+        );
+        assert_eq!(budget.day, time::macros::date!(2026 - 03 - 10));
+        let used = budget.used(datetime!(2026-03-10 05:00 UTC), None);
+        assert_eq!(used, Duration::from_secs(30 * 60));
+        assert_eq!(budget.level(used), Level::Ok);
+    }
+
+    #[test]
+    fn test_notifications_once_a_day() {
+        let now = datetime!(2026-03-10 12:00 UTC);
+        let mut budget = budget(now);
+        let hours = |h: f64| Duration::from_secs_f64(h * 3600.0);
+
+        assert_eq!(budget.next_notification(hours(3.0)), None);
+        assert_eq!(budget.next_change(hours(3.0)), Some(hours(0.6)));
+        assert_eq!(budget.next_change(hours(3.6)), Some(hours(0.4)));
+        assert_eq!(budget.next_change(hours(4.0)), None);
+        assert_eq!(budget.next_notification(hours(3.6)), Some(Level::Warning));
+        assert_eq!(budget.next_notification(hours(3.7)), None);
+        assert_eq!(budget.next_notification(hours(4.0)), Some(Level::Critical));
+        assert_eq!(budget.next_notification(hours(5.0)), None);
+
+        budget.used(datetime!(2026-03-11 04:00 UTC), None);
+        assert_eq!(budget.next_notification(hours(3.6)), Some(Level::Warning));
+    }
+}
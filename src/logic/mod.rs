@@ -1,12 +1,33 @@
+use clap::ValueEnum;
 use hhmmss::Hhmmss;
 use notify_rust::Urgency;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
 use std::time::{Duration, Instant};
+use time::{OffsetDateTime, UtcOffset};
 
 mod alert;
-
-use crate::logic::alert::Alert;
+pub mod budget;
+pub mod overtime;
+pub mod pomodoro;
+pub mod schedule;
+
+use crate::config::BudgetConfig;
+use crate::control::ControlCommand;
+use crate::helpers::{
+    hhmm, idle_detection_timeout, next_countdown_minute, next_minute, open_url, round_up_minute,
+};
+use crate::history::checkpoint::Checkpoint;
+use crate::history::{History, Record, SessionKind};
+use crate::logic::alert::{Alert, NotificationAction, NOTIFICATION_SNOOZE};
+use crate::logic::budget::{Budget, BudgetSettings};
+use crate::logic::overtime::Overtime;
+use crate::logic::pomodoro::Pomodoro;
+use crate::logic::schedule::{Level, Schedule};
+use crate::source::IdleEvent;
+use crate::stats::risk::{self, Risk};
 use crate::Args;
 
 /// Waybar output struct
@@ -17,18 +38,80 @@ struct WaybarOutput {
     tooltip: String,
 }
 
+/// Errors of the logic core
+#[derive(Debug)]
+pub enum LogicError {
+    /// The settings would break the computations
+    Invalid(String),
+    /// The waybar output can't be serialized
+    Serialize(serde_json::Error),
+    /// The waybar output can't be written, waybar is likely gone
+    Output(io::Error),
+}
+
+impl fmt::Display for LogicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicError::Invalid(reason) => write!(f, "Invalid settings: {reason}"),
+            LogicError::Serialize(e) => write!(f, "Can't serialize the waybar output: {e}"),
+            LogicError::Output(e) => write!(f, "Can't write the waybar output: {e}"),
+        }
+    }
+}
+
+impl Error for LogicError {}
+
+/// Report an error preventing the daemon from running, with the error class in waybar, and exit
+pub fn exit_with_error(args: &Args, error: &str) -> ! {
+    eprintln!("{error}");
+    if args.waybar {
+        let output = WaybarOutput {
+            text: args.icon.clone(),
+            class: args.classes.error.clone(),
+            tooltip: error.to_string(),
+        };
+        if let Ok(line) = serde_json::to_string(&output) {
+            println!("{line}");
+        }
+    }
+    std::process::exit(1);
+}
+
 /// Status of the app
 #[derive(Debug)]
 pub enum Status {
     Active(Instant, Duration),
     Idle(Instant, Duration),
+    Break(Instant, Duration),
+}
+
+/// What the daemon tracks
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Eyes filling up with the active time
+    Eyes,
+    /// Pomodoro work intervals and breaks
+    Pomodoro,
+}
+
+/// Time between two computations of the burnout risk
+const RISK_REFRESH: Duration = Duration::from_secs(3600);
+/// Longest time without running, the budget and the overtime follow it
+pub const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Tracking paused from the control socket
+#[derive(Debug, Clone, Copy)]
+pub struct Pause {
+    /// End of the pause, none until resumed
+    until: Option<Instant>,
 }
 
 /// Logic of the app
 #[derive(Debug)]
 pub struct Logic {
     /// App arguments
-    args: Args,
+    pub args: Args,
     /// Status
     pub status: Status,
     /// Number of actual eyes display
@@ -37,96 +120,574 @@ pub struct Logic {
     waybar_output: WaybarOutput,
     /// Popup alert
     pub alert: Alert,
+    /// Sessions history
+    pub history: Option<History>,
+    /// Paused by the user
+    pub paused: Option<Pause>,
+    /// Micro and long break schedules
+    pub schedules: Vec<Schedule>,
+    /// Pomodoro cycle, in pomodoro mode
+    pub pomodoro: Option<Pomodoro>,
+    /// Active time of the day, checked against the daily budget when one is set
+    pub budget: Budget,
+    /// Activity outside the working hours
+    pub overtime: Option<Overtime>,
+    /// Burnout risk computed from the history
+    pub risk: Option<Risk>,
+    /// Last computation of the risk
+    risk_updated: Option<Instant>,
+    /// Why the idle detection doesn't work, none while it does
+    degraded: Option<String>,
+    /// Idle periods and breaks since the start
+    pub breaks: u64,
+    /// The session is locked, the break lasts until it is unlocked
+    locked: bool,
+    /// Local offset, read before any thread is started
+    offset: UtcOffset,
 }
 
-static STATUS_OK: &str = "ok";
-static STATUS_WARNING: &str = "warning";
-static STATUS_CRITICAL: &str = "critical";
+pub static STATUS_OK: &str = "ok";
+pub static STATUS_WARNING: &str = "warning";
+pub static STATUS_CRITICAL: &str = "critical";
+pub static STATUS_PAUSED: &str = "paused";
+pub static STATUS_OVER_BUDGET: &str = "over-budget";
+pub static STATUS_OVERTIME: &str = "overtime";
+pub static STATUS_ERROR: &str = "error";
 
 impl Logic {
-    pub fn new(args: &Args) -> Result<Logic, ()> {
-        let next_send_time = if args.waybar {
-            Duration::from_secs(args.idle_timeout * (args.max_active_sessions + 1))
-        } else {
-            Duration::from_secs(0)
-        };
-
-        let notification_delay_secs = if args.waybar {
-            Duration::from_secs(args.idle_timeout * args.max_active_sessions)
-        } else {
-            Duration::from_secs(args.idle_timeout)
-        };
-
-        let alert = Alert {
-            args: args.clone(),
-            next_send_time,
-            counter_sent: 0,
-            notification_delay_secs,
-            urgency: Urgency::Low,
-            quiet: false,
-        };
+    pub fn new(args: &Args) -> Result<Logic, LogicError> {
+        if args.idle_timeout == 0 || args.max_active_sessions == 0 {
+            return Err(LogicError::Invalid(
+                "idle_timeout and max_active_sessions must be at least 1".into(),
+            ));
+        }
+        let alert = Alert::new(args);
 
         let waybar_output = WaybarOutput {
             text: "".to_owned(),
-            class: "ok".to_owned(),
+            class: args.classes.ok.clone(),
             tooltip: "".to_owned(),
         };
 
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+
         Ok(Logic {
             args: args.clone(),
             status: Status::Active(Instant::now(), Duration::from_secs(0)),
             alert,
             eyes_actual: Vec::new(),
             waybar_output,
+            history: None,
+            paused: None,
+            schedules: Logic::schedules(args, &[]),
+            pomodoro: Logic::pomodoro(args, None),
+            budget: Budget::new(
+                Logic::budget_settings(args),
+                offset,
+                OffsetDateTime::now_utc(),
+            ),
+            overtime: args
+                .working_hours
+                .clone()
+                .map(|hours| Overtime::new(hours, Instant::now())),
+            risk: None,
+            risk_updated: None,
+            degraded: None,
+            breaks: 0,
+            locked: false,
+            offset,
         })
     }
 
+    /// Count the active time of the day recorded in the history
+    pub fn restore_budget(&mut self) {
+        let Some(history) = self.history.as_ref() else {
+            return;
+        };
+        match history.read_all() {
+            Ok(records) => self.budget.restore(&records, OffsetDateTime::now_utc()),
+            Err(e) => eprintln!("Can't read the history for the daily budget: {e}"),
+        }
+    }
+
+    /// Budget settings of `args`, the defaults only count the active time without one
+    fn budget_settings(args: &Args) -> BudgetSettings {
+        args.budget
+            .clone()
+            .unwrap_or_else(|| BudgetSettings::new(&BudgetConfig::default()))
+    }
+
+    /// Pomodoro cycle of `args`, the running one keeps its state
+    fn pomodoro(args: &Args, running: Option<Pomodoro>) -> Option<Pomodoro> {
+        if args.mode != Mode::Pomodoro {
+            return None;
+        }
+        let mut pomodoro =
+            running.unwrap_or_else(|| Pomodoro::new(args.pomodoro.clone(), Instant::now()));
+        pomodoro.settings = args.pomodoro.clone();
+
+        Some(pomodoro)
+    }
+
+    /// Schedules of `args`, the ones already running keep their state
+    fn schedules(args: &Args, running: &[Schedule]) -> Vec<Schedule> {
+        args.schedules
+            .iter()
+            .map(|settings| {
+                let mut schedule = running
+                    .iter()
+                    .find(|schedule| schedule.settings.kind == settings.kind)
+                    .cloned()
+                    .unwrap_or_else(|| Schedule::new(settings.clone(), Instant::now()));
+                schedule.settings = settings.clone();
+                schedule
+            })
+            .collect()
+    }
+
+    /// Apply a new configuration, keeping the current session
+    pub fn reload(&mut self, args: &Args) {
+        self.args = args.clone();
+        self.alert.reload(args);
+        self.schedules = Logic::schedules(args, &self.schedules);
+        self.pomodoro = Logic::pomodoro(args, self.pomodoro.take());
+        self.budget.settings = Logic::budget_settings(args);
+        match (&args.working_hours, self.overtime.as_mut()) {
+            (Some(hours), Some(overtime)) => overtime.hours = hours.clone(),
+            (hours, _) => {
+                self.overtime = hours
+                    .clone()
+                    .map(|hours| Overtime::new(hours, Instant::now()))
+            }
+        }
+        self.waybar_output.class = args.classes.ok.clone();
+    }
+
+    /// Kind and start of the current session
+    fn session(&self) -> (SessionKind, Instant) {
+        match self.status {
+            Status::Active(start, _) => (SessionKind::Active, start),
+            Status::Idle(start, _) => (SessionKind::Idle, start),
+            Status::Break(start, _) => (SessionKind::Break, start),
+        }
+    }
+
+    /// Record the session being finished in the history
+    fn record_session(&mut self) {
+        let (kind, start) = self.session();
+        let duration = start.elapsed();
+        let end = OffsetDateTime::now_utc();
+        if kind == SessionKind::Active {
+            self.budget.add(end - duration, end);
+        }
+        self.record(Record {
+            start: end - duration,
+            end,
+            duration_secs: duration.as_secs(),
+            kind,
+            notifications: self.alert.counter_sent,
+        });
+    }
+
+    fn record(&self, record: Record) {
+        let Some(history) = self.history.as_ref() else {
+            return;
+        };
+        if let Err(e) = history.append(&record) {
+            eprintln!("Can't record the session history: {e}");
+        }
+    }
+
+    /// Snapshot of the current session and alert escalation
+    pub fn checkpoint(&self) -> Checkpoint {
+        let (kind, start) = self.session();
+        let saved_at = OffsetDateTime::now_utc();
+        Checkpoint {
+            kind,
+            start: saved_at - start.elapsed(),
+            saved_at,
+            counter_sent: self.alert.counter_sent,
+            next_send_time: self.alert.next_send_time,
+            notification_delay: self.alert.notification_delay_secs,
+        }
+    }
+
+    /// Continue a checkpointed session, unless the daemon was stopped
+    /// longer than the idle timeout, which counts as a break
+    pub fn resume(&mut self, checkpoint: Checkpoint) -> bool {
+        let now = OffsetDateTime::now_utc();
+        let gap = now - checkpoint.saved_at;
+        let elapsed = Duration::try_from(now - checkpoint.start).ok();
+        let start = elapsed.and_then(|elapsed| Instant::now().checked_sub(elapsed));
+
+        let start = match start {
+            Some(start) if gap <= Duration::from_secs(self.args.idle_timeout) => start,
+            _ => {
+                // the session ended when the daemon stopped
+                self.record(Record {
+                    start: checkpoint.start,
+                    end: checkpoint.saved_at,
+                    duration_secs: (checkpoint.saved_at - checkpoint.start)
+                        .whole_seconds()
+                        .max(0) as u64,
+                    kind: checkpoint.kind,
+                    notifications: checkpoint.counter_sent,
+                });
+                return false;
+            }
+        };
+
+        self.status = match checkpoint.kind {
+            SessionKind::Active => Status::Active(start, Duration::ZERO),
+            SessionKind::Idle => Status::Idle(start, Duration::ZERO),
+            SessionKind::Break => Status::Break(start, Duration::ZERO),
+        };
+        self.alert.counter_sent = checkpoint.counter_sent;
+        self.alert.next_send_time = checkpoint.next_send_time;
+        self.alert.notification_delay_secs = checkpoint.notification_delay;
+
+        true
+    }
+
     pub fn set_resumed(&mut self) {
+        // the user was already away for the detection timeout when the idle session started
+        let away = match self.status {
+            Status::Active(_, _) => None,
+            Status::Idle(start, _) => {
+                Some(start.elapsed() + idle_detection_timeout(self.args.idle_timeout))
+            }
+            Status::Break(start, _) => Some(start.elapsed()),
+        };
+        if let Some(away) = away {
+            let now = Instant::now();
+            for schedule in &mut self.schedules {
+                schedule.end_break(away, now);
+            }
+        }
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            if let Some(event) = pomodoro.came_back() {
+                self.alert.send_pomodoro_notification(event, pomodoro);
+            }
+        }
+        self.record_session();
         self.status = Status::Active(Instant::now(), Duration::from_secs(0));
         self.alert.reset_notifications();
     }
 
     pub fn set_idle(&mut self) {
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.went_away(idle_detection_timeout(self.args.idle_timeout));
+        }
+        self.count_break();
+        self.record_session();
+        self.alert.close_notifications();
         self.status = Status::Idle(Instant::now(), Duration::from_secs(0));
         self.alert.reset_notifications();
     }
 
-    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Definitive break, like a suspend or a locked session
+    pub fn set_break(&mut self) {
+        self.count_break();
+        self.record_session();
+        self.alert.close_notifications();
+        self.status = Status::Break(Instant::now(), Duration::from_secs(0));
+        self.eyes_actual.clear();
+        self.alert.reset_notifications();
+    }
+
+    /// An idle period or a break starts, unless the user was away already
+    fn count_break(&mut self) {
+        if matches!(self.status, Status::Active(_, _)) {
+            self.breaks += 1;
+        }
+    }
+
+    /// Number of eyes shown
+    pub fn eyes(&self) -> usize {
+        self.eyes_actual.len()
+    }
+
+    pub fn handle_event(&mut self, event: IdleEvent) {
+        match event {
+            IdleEvent::Locked => self.locked = true,
+            IdleEvent::Unlocked => self.locked = false,
+            _ => {}
+        }
+        match (event, &self.status) {
+            // moving the mouse on the lock screen doesn't end the break
+            (IdleEvent::Resumed, _) if self.locked => {}
+            (IdleEvent::Locked, Status::Break(_, _)) => {}
+            (IdleEvent::Locked, _) => self.set_break(),
+            (IdleEvent::Unlocked, Status::Active(_, _)) => {}
+            (IdleEvent::Unlocked, _) => self.set_resumed(),
+            // several sources can report the same transition
+            (IdleEvent::Resumed, Status::Active(_, _)) => {}
+            (IdleEvent::Idle, Status::Idle(_, _)) => {}
+            (IdleEvent::Break, Status::Break(_, _)) => {}
+            // being idle during a break doesn't end it
+            (IdleEvent::Idle, Status::Break(_, _)) => {}
+            (IdleEvent::Idle, _) => self.set_idle(),
+            (IdleEvent::Resumed, _) => self.set_resumed(),
+            (IdleEvent::Break, _) => self.set_break(),
+        }
+    }
+
+    /// Apply a command received on the control socket, the reply is sent back to the client
+    pub fn control(&mut self, command: ControlCommand) -> Result<String, String> {
+        match command {
+            ControlCommand::Status => {}
+            ControlCommand::Pause { duration } => {
+                self.paused = Some(Pause {
+                    until: duration.map(|duration| Instant::now() + duration),
+                });
+            }
+            ControlCommand::Resume => match (self.paused, &self.status) {
+                (Some(_), _) => self.unpause(),
+                (None, Status::Break(_, _)) => self.set_resumed(),
+                (None, _) => return Err("Nothing to resume".into()),
+            },
+            ControlCommand::Snooze { duration } => {
+                let Status::Active(start, _) = self.status else {
+                    return Err("Notifications are only sent while active".into());
+                };
+                self.alert.snooze(start.elapsed() + duration);
+            }
+            ControlCommand::Reset => {
+                self.eyes_actual.clear();
+                for schedule in &mut self.schedules {
+                    schedule.restart(Instant::now());
+                }
+                if let Some(pomodoro) = self.pomodoro.as_mut() {
+                    *pomodoro = Pomodoro::new(pomodoro.settings.clone(), Instant::now());
+                }
+                self.set_resumed();
+            }
+            ControlCommand::BreakNow => self.set_break(),
+        }
+
+        Ok(self.describe())
+    }
+
+    /// Human readable state, for the control clients
+    fn describe(&self) -> String {
+        let (kind, start) = self.session();
+        let mut description = match kind {
+            SessionKind::Active => format!(
+                "Active for {}, {} notification(s) sent",
+                start.elapsed().hhmmss(),
+                self.alert.counter_sent
+            ),
+            SessionKind::Idle => format!("Idle for {}", start.elapsed().hhmmss()),
+            SessionKind::Break => format!("On a break for {}", start.elapsed().hhmmss()),
+        };
+        match self.paused {
+            Some(Pause { until: Some(until) }) => description.push_str(&format!(
+                "\nPaused for another {}",
+                until.saturating_duration_since(Instant::now()).hhmmss()
+            )),
+            Some(Pause { until: None }) => description.push_str("\nPaused until resumed"),
+            None => {}
+        }
+        if let Some(pomodoro) = &self.pomodoro {
+            description.push('\n');
+            description.push_str(&pomodoro.describe());
+        }
+
+        description
+    }
+
+    /// End the pause, a new active session starts if the user is still there
+    fn unpause(&mut self) {
+        self.paused = None;
+        if let Status::Active(_, _) = self.status {
+            self.eyes_actual.clear();
+            self.set_resumed();
+        }
+    }
+
+    /// Apply an action clicked in a notification
+    pub fn handle_action(&mut self, action: NotificationAction) {
+        match action {
+            NotificationAction::Snooze => {
+                if let Status::Active(start, _) = self.status {
+                    self.alert.snooze(start.elapsed() + NOTIFICATION_SNOOZE);
+                }
+            }
+            NotificationAction::TakeBreak => self.set_break(),
+            NotificationAction::OpenGuide(url) => open_url(&url),
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), LogicError> {
+        for action in self.alert.pending_actions() {
+            self.handle_action(action);
+        }
         self.update()?;
         self.run_on_state()?;
+        self.show_degraded();
         self.show_debug();
         self.show_waybar()?;
         Ok(())
     }
 
-    fn run_on_state(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Time before the output or the notifications change, the main loop can sleep until then
+    pub fn next_deadline(&self) -> Duration {
+        let now = Instant::now();
+        let mut deadlines = vec![MAX_SLEEP];
+        match self.paused {
+            Some(Pause { until: Some(until) }) => {
+                let remaining = until.saturating_duration_since(now);
+                deadlines.push(remaining);
+                if self.args.waybar {
+                    deadlines.push(next_countdown_minute(remaining));
+                }
+            }
+            Some(Pause { until: None }) => {}
+            None => {
+                deadlines.extend(self.next_changes(now));
+                let used = self.active_used();
+                deadlines.extend(used.and_then(|used| self.budget.next_change(used)));
+                if self.args.waybar {
+                    deadlines.extend(self.next_clock_changes(now));
+                    deadlines.extend(used.map(next_minute));
+                }
+            }
+        }
+
+        deadlines.into_iter().min().unwrap_or(MAX_SLEEP)
+    }
+
+    /// Time before the clocks shown to the minute in waybar change
+    fn next_clock_changes(&self, now: Instant) -> Vec<Duration> {
+        let (_, start) = self.session();
+        let mut changes = vec![next_minute(now.saturating_duration_since(start))];
+        if let Some(pomodoro) = &self.pomodoro {
+            let away = !matches!(self.status, Status::Active(_, _));
+            changes.extend(pomodoro.next_clock_change(away));
+        }
+        changes.extend(
+            self.schedules
+                .iter()
+                .map(|schedule| next_minute(schedule.elapsed(now))),
+        );
+
+        changes
+    }
+
+    /// Time before the eyes, the notifications, the schedules or the pomodoro change
+    fn next_changes(&self, now: Instant) -> Vec<Duration> {
+        let mut changes = Vec::new();
+        if let Some(pomodoro) = &self.pomodoro {
+            let away = !matches!(self.status, Status::Active(_, _));
+            changes.extend(pomodoro.next_change(away));
+            return changes;
+        }
+
+        let step = Duration::from_secs(self.args.idle_timeout);
+        // a huge number of sessions never shows all the eyes
+        let eyes_time = |eyes: u64| {
+            u32::try_from(eyes)
+                .ok()
+                .and_then(|eyes| step.checked_mul(eyes))
+                .unwrap_or(Duration::MAX)
+        };
+        let all_eyes = eyes_time(self.args.max_active_sessions);
+        let next_eye = |elapsed: Duration| {
+            let eyes = elapsed.as_secs() / self.args.idle_timeout;
+            (elapsed < all_eyes).then(|| eyes_time(eyes + 1) - elapsed)
+        };
+        match self.status {
+            Status::Active(start, _) => {
+                let elapsed = now.saturating_duration_since(start);
+                changes.extend(next_eye(elapsed));
+                let first = if self.args.waybar { all_eyes } else { step };
+                let next_send = self.alert.next_send_time.max(first);
+                if !self.args.no_notify && next_send > elapsed {
+                    changes.push(next_send - elapsed);
+                }
+            }
+            Status::Idle(start, _) => {
+                changes.extend(next_eye(now.saturating_duration_since(start)))
+            }
+            Status::Break(_, _) => {}
+        }
+        changes.extend(
+            self.schedules
+                .iter()
+                .filter_map(|schedule| schedule.next_change(now)),
+        );
+
+        changes
+    }
+
+    /// Report the idle detection problems of the sources, none once they work again
+    pub fn set_degraded(&mut self, problem: Option<String>) {
+        if problem == self.degraded {
+            return;
+        }
+        match &problem {
+            Some(problem) => eprintln!("Idle detection unavailable: {problem}"),
+            None => eprintln!("Idle detection works again"),
+        }
+        self.degraded = problem;
+    }
+
+    /// The output can't be trusted while the idle detection is degraded
+    fn show_degraded(&mut self) {
+        let Some(problem) = &self.degraded else {
+            return;
+        };
+        self.waybar_output.class = self.args.classes.error.clone();
+        self.waybar_output.tooltip = format!(
+            "Idle detection unavailable: {problem}\n{}",
+            self.waybar_output.tooltip
+        );
+    }
+
+    fn run_on_state(&mut self) -> Result<(), LogicError> {
+        self.compute_overtime();
+        self.compute_risk();
+        if let Some(pause) = self.paused {
+            return self.compute_paused(pause);
+        }
+        if self.pomodoro.is_some() {
+            return self.compute_pomodoro();
+        }
         match self.status {
             Status::Active(_start, elapsed) => self.compute_active(elapsed)?,
             Status::Idle(_start, elapsed) => self.compute_idle(elapsed)?,
+            Status::Break(_start, elapsed) => self.compute_break(elapsed)?,
         }
         Ok(())
     }
 
-    fn show_waybar(&mut self) -> Result<bool, Box<dyn Error>> {
+    fn show_waybar(&mut self) -> Result<bool, LogicError> {
         if !self.args.waybar {
             return Ok(false);
         }
 
-        println!("{}", serde_json::to_string(&self.waybar_output)?);
+        let output = serde_json::to_string(&self.waybar_output).map_err(LogicError::Serialize)?;
+        writeln!(io::stdout(), "{output}").map_err(LogicError::Output)?;
 
         Ok(true)
     }
 
-    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+    fn update(&mut self) -> Result<(), LogicError> {
+        if let Some(Pause { until: Some(until) }) = self.paused {
+            if Instant::now() >= until {
+                self.unpause();
+            }
+        }
         self.status = match self.status {
             Status::Active(start, _) => Status::Active(start, Instant::now() - start),
             Status::Idle(start, _) => Status::Idle(start, Instant::now() - start),
+            Status::Break(start, _) => Status::Break(start, Instant::now() - start),
         };
 
         Ok(())
     }
 
-    fn compute_active(&mut self, elapsed: Duration) -> Result<(), Box<dyn Error>> {
+    fn compute_active(&mut self, elapsed: Duration) -> Result<(), LogicError> {
         let expected_number_of_eyes = self
             .args
             .max_active_sessions
@@ -152,32 +713,238 @@ impl Logic {
             self.alert.send_notification(urgency, &self.status);
         }
 
-        self.waybar_output.class = if expected_number_of_eyes == 0 {
-            STATUS_OK.to_string()
+        let level = if expected_number_of_eyes == 0 {
+            Level::Ok
         } else if expected_number_of_eyes < self.args.max_active_sessions {
-            STATUS_WARNING.to_string()
+            Level::Warning
         } else {
-            STATUS_CRITICAL.to_string()
+            Level::Critical
         };
-
-        self.waybar_output.tooltip = format!("You didn't take a break for {}", elapsed.hhmmss());
+        // the most urgent break wins
+        let level = level.max(self.compute_schedules());
+        let budget = self.compute_budget();
+        self.waybar_output.class = self.class(level, &budget);
+
+        let now = Instant::now();
+        self.waybar_output.tooltip =
+            std::iter::once(format!("You didn't take a break for {}", hhmm(elapsed)))
+                .chain(self.schedules.iter().map(|schedule| schedule.describe(now)))
+                .collect::<Vec<_>>()
+                .join("\n");
+        self.push_tooltip_lines(budget);
         self.waybar_output.text = self.eyes_actual.join(" ");
 
         Ok(())
     }
 
-    fn compute_idle(&mut self, elapsed: Duration) -> Result<(), Box<dyn Error>> {
+    /// Notify the due schedules, and return the most urgent level
+    fn compute_schedules(&mut self) -> Level {
+        let now = Instant::now();
+        let mut level = Level::Ok;
+        for schedule in &mut self.schedules {
+            if let Some(notified) = schedule.next_notification(now) {
+                self.alert.send_schedule_notification(
+                    &schedule.settings,
+                    notified,
+                    schedule.elapsed(now),
+                );
+            }
+            level = level.max(schedule.level(now));
+        }
+        level
+    }
+
+    /// Start of the current active session, on the wall clock
+    fn active_since(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self.status {
+            Status::Active(start, _) => Some(now - start.elapsed()),
+            Status::Idle(_, _) | Status::Break(_, _) => None,
+        }
+    }
+
+    /// Active time of the day counted against the budget, none while it doesn't grow
+    fn active_used(&self) -> Option<Duration> {
+        self.args.budget.as_ref()?;
+        let now = OffsetDateTime::now_utc();
+        let active_since = self.active_since(now)?;
+        Some(self.budget.peek(now, Some(active_since)))
+    }
+
+    /// Active time of the day, the current session included
+    pub fn active_today(&mut self) -> Duration {
+        let now = OffsetDateTime::now_utc();
+        let active_since = self.active_since(now);
+        self.budget.used(now, active_since)
+    }
+
+    /// Notify the budget levels reached while active, and return the budget level and tooltip line
+    fn compute_budget(&mut self) -> Option<(Level, String)> {
+        self.args.budget.as_ref()?;
+        let now = OffsetDateTime::now_utc();
+        let active_since = self.active_since(now);
+        let budget = &mut self.budget;
+        let used = budget.used(now, active_since);
+        if active_since.is_some() {
+            if let Some(level) = budget.next_notification(used) {
+                self.alert
+                    .send_budget_notification(&budget.settings, level, used);
+            }
+        }
+
+        Some((budget.level(used), budget.describe(used)))
+    }
+
+    /// Track the activity outside the working hours, and notify it
+    fn compute_overtime(&mut self) {
+        let active = self.paused.is_none() && matches!(self.status, Status::Active(_, _));
+        let Some(overtime) = self.overtime.as_mut() else {
+            return;
+        };
+        let local = OffsetDateTime::now_utc().to_offset(self.offset);
+        if let Some(active_for) = overtime.update(Instant::now(), local, active) {
+            self.alert
+                .send_overtime_notification(&overtime.hours, local, active_for);
+        }
+    }
+
+    /// Compute the burnout risk from the history every hour, and send the weekly summary
+    fn compute_risk(&mut self) {
+        if !self.args.risk_tooltip && !self.args.weekly_summary {
+            return;
+        }
+        if self
+            .risk_updated
+            .is_some_and(|updated| updated.elapsed() < RISK_REFRESH)
+        {
+            return;
+        }
+        let Some(history) = self.history.as_ref() else {
+            return;
+        };
+        self.risk_updated = Some(Instant::now());
+        let now = OffsetDateTime::now_utc().to_offset(self.offset);
+        let risk = match history.read_all() {
+            Ok(records) => Risk::compute(&records, now),
+            Err(e) => {
+                eprintln!("Can't read the history for the burnout risk: {e}");
+                return;
+            }
+        };
+
+        // only the instance writing the history sends it
+        let active = self.paused.is_none() && matches!(self.status, Status::Active(_, _));
+        if self.args.weekly_summary && active && !history.read_only {
+            if let Some(path) = risk::summary_path() {
+                if risk::summary_due(risk::last_summary(&path), now.date()) {
+                    self.alert.send_risk_summary(&risk);
+                    if let Err(e) = risk::save_summary(&path, now.date()) {
+                        eprintln!("Can't save the weekly summary date: {e}");
+                    }
+                }
+            }
+        }
+        self.risk = Some(risk);
+    }
+
+    /// Class of the most urgent level, the overtime and an exceeded budget have their own
+    /// unless the break is critical
+    fn class(&self, level: Level, budget: &Option<(Level, String)>) -> String {
+        let overtime = self.overtime.as_ref().is_some_and(Overtime::is_overtime);
+        if overtime && level < Level::Critical {
+            return self.args.classes.overtime.clone();
+        }
+        match budget {
+            Some((Level::Critical, _)) if level < Level::Critical => {
+                self.args.classes.over_budget.clone()
+            }
+            Some((budget, _)) => self.args.classes.for_level(level.max(*budget)),
+            None => self.args.classes.for_level(level),
+        }
+    }
+
+    /// Append the budget, overtime and risk lines to the tooltip
+    fn push_tooltip_lines(&mut self, budget: Option<(Level, String)>) {
+        let overtime = self.overtime.as_ref().and_then(Overtime::describe);
+        let risk = self
+            .risk
+            .as_ref()
+            .filter(|_| self.args.risk_tooltip)
+            .map(Risk::describe);
+        for line in budget
+            .map(|(_, line)| line)
+            .into_iter()
+            .chain(overtime)
+            .chain(risk)
+        {
+            self.waybar_output.tooltip.push('\n');
+            self.waybar_output.tooltip.push_str(&line);
+        }
+    }
+
+    fn compute_idle(&mut self, elapsed: Duration) -> Result<(), LogicError> {
         let max_eyes = self.args.max_active_sessions;
         let max_seconds = max_eyes * self.args.idle_timeout;
 
-        let new_eyes_to_remove = (elapsed.as_secs() * max_eyes) / max_seconds;
+        let new_eyes_to_remove = elapsed.as_secs().saturating_mul(max_eyes) / max_seconds;
 
         let new_eyes = max_eyes.saturating_sub(new_eyes_to_remove);
 
         self.eyes_actual = (0..new_eyes).map(|_| self.args.icon.to_string()).collect();
 
-        self.waybar_output.class = STATUS_OK.into();
-        self.waybar_output.tooltip = format!("You are idle since {}", elapsed.hhmmss());
+        self.waybar_output.class = self.args.classes.ok.clone();
+        self.waybar_output.tooltip = format!("You are idle since {}", hhmm(elapsed));
+        let budget = self.compute_budget();
+        self.push_tooltip_lines(budget);
+        self.waybar_output.text = self.eyes_actual.join(" ");
+        Ok(())
+    }
+
+    fn compute_break(&mut self, elapsed: Duration) -> Result<(), LogicError> {
+        self.eyes_actual.clear();
+
+        self.waybar_output.class = self.args.classes.ok.clone();
+        self.waybar_output.tooltip = format!("You are taking a break since {}", hhmm(elapsed));
+        let budget = self.compute_budget();
+        self.push_tooltip_lines(budget);
+        self.waybar_output.text = self.eyes_actual.join(" ");
+        Ok(())
+    }
+
+    fn compute_pomodoro(&mut self) -> Result<(), LogicError> {
+        let away = !matches!(self.status, Status::Active(_, _));
+        let Some(pomodoro) = self.pomodoro.as_mut() else {
+            return Ok(());
+        };
+        if let Some(event) = pomodoro.update(Instant::now(), away) {
+            self.alert.send_pomodoro_notification(event, pomodoro);
+        }
+
+        let (level, tooltip, text) = (pomodoro.level(), pomodoro.describe(), pomodoro.text());
+
+        let budget = self.compute_budget();
+        self.waybar_output.class = self.class(level, &budget);
+        self.waybar_output.tooltip = tooltip;
+        self.push_tooltip_lines(budget);
+        self.waybar_output.text = text;
+        Ok(())
+    }
+
+    fn compute_paused(&mut self, pause: Pause) -> Result<(), LogicError> {
+        self.eyes_actual.clear();
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.skip(Instant::now());
+        }
+
+        self.waybar_output.class = self.args.classes.paused.clone();
+        self.waybar_output.tooltip = match pause.until {
+            Some(until) => format!(
+                "Paused for another {}",
+                hhmm(round_up_minute(
+                    until.saturating_duration_since(Instant::now())
+                ))
+            ),
+            None => "Paused until resumed".to_string(),
+        };
         self.waybar_output.text = self.eyes_actual.join(" ");
         Ok(())
     }
@@ -193,6 +960,9 @@ impl Logic {
             Status::Active(_, elapsed) => {
                 eprintln!("Is active since {}", elapsed.hhmmss());
             }
+            Status::Break(_, elapsed) => {
+                eprintln!("Is on a break since {}", elapsed.hhmmss());
+            }
         }
     }
 }
@@ -200,6 +970,10 @@ impl Logic {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{BudgetConfig, ScheduleConfig, WorkingHoursConfig};
+    use crate::logic::budget::BudgetSettings;
+    use crate::logic::overtime::WorkingHours;
+    use crate::logic::schedule::{ScheduleKind, ScheduleSettings};
 
     #[test]
     fn test_waybar_output_active_eye_increase_over_time() {
@@ -210,6 +984,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -220,30 +995,30 @@ mod tests {
         assert_eq!(logic.waybar_output.class, STATUS_OK);
 
         logic.status = Status::Active(start, Duration::from_secs(0));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, "");
 
         logic.status = Status::Active(start, Duration::from_secs(60));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         eyes.push(args.icon.to_string());
         assert_eq!(logic.waybar_output.class, STATUS_WARNING);
         assert_eq!(logic.waybar_output.text, eyes.join(" "));
 
         logic.status = Status::Active(start, Duration::from_secs(120));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         eyes.push(args.icon.to_string());
         assert_eq!(logic.waybar_output.class, STATUS_WARNING);
         assert_eq!(logic.waybar_output.text, eyes.join(" "));
 
         logic.status = Status::Active(start, Duration::from_secs(180));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         eyes.push(args.icon.to_string());
         assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
         assert_eq!(logic.waybar_output.text, eyes.join(" "));
 
         logic.status = Status::Active(start, Duration::from_secs(240));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
         assert_eq!(logic.waybar_output.text, eyes.join(" "));
     }
@@ -257,6 +1032,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -266,7 +1042,7 @@ mod tests {
         logic.status = Status::Idle(Instant::now(), Duration::from_secs(60));
 
         let expected_waybar = format!("{} {}", args.icon, args.icon);
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
     }
@@ -280,6 +1056,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -288,8 +1065,8 @@ mod tests {
         logic.eyes_actual = vec![args.icon.clone(), args.icon.clone(), args.icon.clone()];
         logic.status = Status::Idle(Instant::now(), Duration::from_secs(120));
 
-        let expected_waybar = format!("{}", args.icon);
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        let expected_waybar = args.icon.to_string();
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
     }
@@ -304,6 +1081,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -312,14 +1090,14 @@ mod tests {
         logic.eyes_actual = vec![args.icon.clone(), args.icon.clone(), args.icon.clone()];
         logic.status = Status::Idle(Instant::now(), Duration::from_secs(120));
 
-        let expected_waybar = format!("{}", args.icon);
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        let expected_waybar = args.icon.to_string();
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
 
         logic.status = Status::Active(Instant::now(), Duration::from_secs(60));
-        let expected_waybar = format!("{}", args.icon);
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        let expected_waybar = args.icon.to_string();
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_WARNING);
         assert_eq!(logic.waybar_output.text, expected_waybar);
     }
@@ -333,6 +1111,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -342,19 +1121,19 @@ mod tests {
 
         let expected_waybar = format!("{} {}", args.icon, args.icon);
         logic.status = Status::Idle(start, Duration::from_secs(119));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
 
-        let expected_waybar = format!("{}", args.icon);
+        let expected_waybar = args.icon.to_string();
         logic.status = Status::Idle(start, Duration::from_secs(120));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
 
-        let expected_waybar = format!("{}", args.icon);
+        let expected_waybar = args.icon.to_string();
         logic.status = Status::Idle(start, Duration::from_secs(121));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, expected_waybar);
     }
@@ -368,6 +1147,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -377,7 +1157,7 @@ mod tests {
         logic.eyes_actual = vec![args.icon.clone(), args.icon.clone(), args.icon.clone()];
         logic.status = Status::Idle(start, Duration::from_secs(180));
 
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.waybar_output.class, STATUS_OK);
         assert_eq!(logic.waybar_output.text, "");
     }
@@ -391,6 +1171,7 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
 
         let mut logic = Logic::new(&args).unwrap();
@@ -399,61 +1180,61 @@ mod tests {
 
         // after 0s
         logic.status = Status::Active(start, Duration::from_secs(0));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(240));
 
         // after 60s
         logic.status = Status::Active(start, Duration::from_secs(60));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(240));
 
         // after 120s
         logic.status = Status::Active(start, Duration::from_secs(120));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(240));
 
         // after 180s
         logic.status = Status::Active(start, Duration::from_secs(180));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(240));
 
         // after 240s
         logic.status = Status::Active(start, Duration::from_secs(240));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(330));
 
         // after 242s
         logic.status = Status::Active(start, Duration::from_secs(242));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(330));
 
         // after 243s
         logic.status = Status::Active(start, Duration::from_secs(243));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(330));
 
         // after 330s
         logic.status = Status::Active(start, Duration::from_secs(330));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 2);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(375));
 
         // after 375s, this start to be more aggressive
         logic.status = Status::Active(start, Duration::from_secs(375));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 3);
         assert_eq!(logic.alert.next_send_time, Duration::from_millis(397500));
 
         // after 397.5s, this start to be more aggressive
         logic.status = Status::Active(start, Duration::from_millis(397500));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 4);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(420));
     }
@@ -467,77 +1248,702 @@ mod tests {
             max_active_sessions: 3,
             no_notify: false,
             icon: "O".to_owned(),
+            ..Default::default()
         };
         let mut logic = Logic::new(&args).unwrap();
         logic.alert.quiet = true;
         let start = Instant::now();
         // after 0s
         logic.status = Status::Active(start, Duration::from_secs(0));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(0));
 
         // after 1s
         logic.status = Status::Active(start, Duration::from_secs(1));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(0));
 
         // after 60s
         logic.status = Status::Active(start, Duration::from_secs(60));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(120));
 
         // after 61s
         logic.status = Status::Active(start, Duration::from_secs(61));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(120));
 
         // after 62s
         logic.status = Status::Active(start, Duration::from_secs(62));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(120));
 
         // after 90s
         logic.status = Status::Active(start, Duration::from_secs(90));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 1);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(120));
 
         // after 120s
         logic.status = Status::Active(start, Duration::from_secs(120));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 2);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(180));
 
         // after 130s
         logic.status = Status::Active(start, Duration::from_secs(130));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 2);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(180));
 
         // after 180s
         logic.status = Status::Active(start, Duration::from_secs(180));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 3);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(240));
 
         // after 240s, we start the harassment from here
         logic.status = Status::Active(start, Duration::from_secs(240));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 4);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(270));
 
         // after 270s
         logic.status = Status::Active(start, Duration::from_secs(270));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 5);
         assert_eq!(logic.alert.next_send_time, Duration::from_secs(285));
     }
 
+    #[test]
+    fn test_break_resets_eyes_and_notifications() {
+        let args = Args {
+            debug: false,
+            idle_timeout: 60,
+            waybar: true,
+            max_active_sessions: 3,
+            no_notify: false,
+            icon: "O".to_owned(),
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(240));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 1);
+        assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
+
+        logic.handle_event(IdleEvent::Break);
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 0);
+        assert_eq!(logic.waybar_output.class, STATUS_OK);
+        assert_eq!(logic.waybar_output.text, "");
+
+        // the compositor going idle while locked doesn't end the break
+        logic.handle_event(IdleEvent::Idle);
+        assert!(matches!(logic.status, Status::Break(_, _)));
+
+        logic.handle_event(IdleEvent::Resumed);
+        assert!(matches!(logic.status, Status::Active(_, _)));
+    }
+
+    #[test]
+    fn test_lock_break_lasts_until_unlocked() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        logic.handle_event(IdleEvent::Locked);
+        assert!(matches!(logic.status, Status::Break(_, _)));
+        // input on the lock screen after a wake up
+        logic.handle_event(IdleEvent::Resumed);
+        assert!(matches!(logic.status, Status::Break(_, _)));
+
+        logic.handle_event(IdleEvent::Unlocked);
+        assert!(matches!(logic.status, Status::Active(_, _)));
+
+        // a suspend without a lock ends with the next input
+        logic.handle_event(IdleEvent::Break);
+        logic.handle_event(IdleEvent::Resumed);
+        assert!(matches!(logic.status, Status::Active(_, _)));
+    }
+
+    #[test]
+    fn test_pause_and_resume_from_the_control_socket() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            icon: "O".to_owned(),
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(240));
+        assert!(logic
+            .control(ControlCommand::Pause { duration: None })
+            .is_ok());
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 0);
+        assert_eq!(logic.waybar_output.class, STATUS_PAUSED);
+        assert_eq!(logic.waybar_output.text, "");
+
+        // a new session starts after the pause
+        assert!(logic.control(ControlCommand::Resume).is_ok());
+        assert!(logic.paused.is_none());
+        assert!(matches!(logic.status, Status::Active(start, _) if start.elapsed().as_secs() < 1));
+        assert!(logic.control(ControlCommand::Resume).is_err());
+
+        // an expired pause ends by itself
+        logic.paused = Some(Pause {
+            until: Some(Instant::now()),
+        });
+        assert!(logic.update().is_ok());
+        assert!(logic.paused.is_none());
+
+        assert!(logic.control(ControlCommand::BreakNow).is_ok());
+        assert!(matches!(logic.status, Status::Break(_, _)));
+        assert!(logic.control(ControlCommand::Resume).is_ok());
+        assert!(matches!(logic.status, Status::Active(_, _)));
+    }
+
+    #[test]
+    fn test_snooze_delays_the_next_notification() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        let start = Instant::now() - Duration::from_secs(180);
+        logic.status = Status::Active(start, Duration::from_secs(180));
+        let reply = logic.control(ControlCommand::Snooze {
+            duration: Duration::from_secs(600),
+        });
+        assert!(reply.unwrap().starts_with("Active for 00:03:00"));
+
+        logic.status = Status::Active(start, Duration::from_secs(240));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 0);
+
+        logic.status = Status::Active(start, Duration::from_secs(781));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 1);
+
+        logic.set_idle();
+        assert!(logic
+            .control(ControlCommand::Snooze {
+                duration: Duration::from_secs(600)
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_notification_actions() {
+        let args = Args {
+            idle_timeout: 60,
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(60));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 1);
+
+        logic.handle_action(NotificationAction::Snooze);
+        assert!(logic.alert.next_send_time >= NOTIFICATION_SNOOZE);
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(300));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 1);
+
+        logic.handle_action(NotificationAction::TakeBreak);
+        assert!(matches!(logic.status, Status::Break(_, _)));
+        assert_eq!(logic.alert.counter_sent, 0);
+    }
+
+    #[test]
+    fn test_schedules() {
+        let micro = ScheduleSettings::new(ScheduleKind::Micro, &ScheduleConfig::default());
+        let long = ScheduleSettings::new(ScheduleKind::Long, &ScheduleConfig::default());
+        let args = Args {
+            idle_timeout: 600,
+            waybar: true,
+            schedules: vec![micro, long],
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        let start = Instant::now();
+
+        // the micro break is due before any eye shows up
+        logic.schedules[0].restart(start - Duration::from_secs(21 * 60));
+        logic.status = Status::Active(start, Duration::from_secs(60));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_WARNING);
+        assert_eq!(logic.waybar_output.text, "");
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .contains("A micro break is due for 00:01"));
+
+        logic.schedules[1].restart(start - Duration::from_secs(91 * 60));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
+
+        // a short idle period satisfies the micro break only
+        logic.status = Status::Idle(Instant::now(), Duration::ZERO);
+        logic.set_resumed();
+        assert_eq!(logic.schedules[0].level(Instant::now()), Level::Ok);
+        assert_eq!(logic.schedules[1].level(Instant::now()), Level::Critical);
+
+        logic.status = Status::Break(Instant::now() - Duration::from_secs(600), Duration::ZERO);
+        logic.set_resumed();
+        assert_eq!(logic.schedules[1].level(Instant::now()), Level::Ok);
+
+        // reloading keeps the running schedules
+        logic.schedules[0].restart(start - Duration::from_secs(21 * 60));
+        logic.reload(&args);
+        assert_eq!(logic.schedules[0].level(Instant::now()), Level::Warning);
+        logic.reload(&Args {
+            schedules: Vec::new(),
+            ..args
+        });
+        assert!(logic.schedules.is_empty());
+    }
+
+    #[test]
+    fn test_pomodoro_mode() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            mode: Mode::Pomodoro,
+            icon: "O".to_owned(),
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        // the phase replaces the eyes, without the eyes notifications
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(600));
+        assert!(logic.run_on_state().is_ok());
+        assert!(logic.waybar_output.text.starts_with("🍅 2"));
+        assert_eq!(logic.waybar_output.class, STATUS_OK);
+        assert_eq!(logic.alert.counter_sent, 0);
+        assert!(logic
+            .control(ControlCommand::Status)
+            .unwrap()
+            .contains("Pomodoro 1/4"));
+
+        // leaving the eyes mode drops the cycle
+        logic.reload(&Args {
+            mode: Mode::Eyes,
+            ..args
+        });
+        assert!(logic.pomodoro.is_none());
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.text, "O O O");
+    }
+
+    #[test]
+    fn test_daily_budget() {
+        let config = BudgetConfig {
+            daily: Some(3600),
+            ..BudgetConfig::default()
+        };
+        let args = Args {
+            idle_timeout: 600,
+            waybar: true,
+            budget: Some(BudgetSettings::new(&config)),
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        logic.history = Some(History::new(dir.path()));
+
+        // short sessions with breaks in between still add up
+        for _ in 0..2 {
+            logic.status =
+                Status::Active(Instant::now() - Duration::from_secs(1500), Duration::ZERO);
+            logic.set_idle();
+        }
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(600), Duration::ZERO);
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_OVER_BUDGET);
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .contains("Active 01:00 today, out of 01:00\n"));
+
+        // the eyes still win when critical
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(1800));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
+
+        // a restart counts the recorded sessions
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        logic.history = Some(History::new(dir.path()));
+        logic.restore_budget();
+        logic.status = Status::Idle(Instant::now(), Duration::ZERO);
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_OK);
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .contains("Active 00:50 today, out of 01:00\n"));
+    }
+
+    #[test]
+    fn test_next_deadline() {
+        let args = Args {
+            idle_timeout: 60,
+            max_active_sessions: 3,
+            waybar: true,
+            ..Default::default()
+        };
+        let mut logic = Logic::new(&args).unwrap();
+        let around = |deadline: Duration, secs: u64| {
+            deadline <= Duration::from_secs(secs) && deadline > Duration::from_secs(secs - 2)
+        };
+        let next_change = |logic: &Logic| {
+            logic
+                .next_changes(Instant::now())
+                .into_iter()
+                .min()
+                .unwrap_or(MAX_SLEEP)
+        };
+
+        // the next eye
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(90), Duration::ZERO);
+        assert!(around(next_change(&logic), 30));
+        // the next notification
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(200), Duration::ZERO);
+        assert!(around(next_change(&logic), 40));
+        // an eye going away
+        logic.status = Status::Idle(Instant::now() - Duration::from_secs(150), Duration::ZERO);
+        assert!(around(next_change(&logic), 30));
+        logic.status = Status::Break(Instant::now() - Duration::from_secs(45), Duration::ZERO);
+        assert_eq!(next_change(&logic), MAX_SLEEP);
+        // the clock of the tooltip shows one more minute
+        assert!(around(logic.next_deadline(), 15));
+        logic.args.waybar = false;
+        assert_eq!(logic.next_deadline(), MAX_SLEEP);
+
+        logic.paused = Some(Pause {
+            until: Some(Instant::now() + Duration::from_secs(10)),
+        });
+        assert!(around(logic.next_deadline(), 10));
+        logic.paused = Some(Pause {
+            until: Some(Instant::now() + Duration::from_secs(130)),
+        });
+        assert_eq!(logic.next_deadline(), MAX_SLEEP);
+        // the countdown of the tooltip shows one minute less
+        logic.args.waybar = true;
+        assert!(around(logic.next_deadline(), 10));
+        logic.paused = Some(Pause { until: None });
+        assert_eq!(logic.next_deadline(), MAX_SLEEP);
+
+        // the countdown of the pomodoro
+        let args = Args {
+            mode: Mode::Pomodoro,
+            ..args
+        };
+        let logic = Logic::new(&args).unwrap();
+        assert!(around(logic.next_deadline(), 60));
+
+        // more sessions than a u32
+        let args = Args {
+            mode: Mode::Eyes,
+            max_active_sessions: u64::from(u32::MAX) + 2,
+            ..args
+        };
+        let mut logic = Logic::new(&args).unwrap();
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(90), Duration::ZERO);
+        assert!(around(next_change(&logic), 30));
+    }
+
+    #[test]
+    fn test_risk_in_tooltip() {
+        let args = Args {
+            idle_timeout: 600,
+            waybar: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        logic.history = Some(History::new(dir.path()));
+
+        // whatever the day and time of the test, an idle session adds no risk
+        logic.status = Status::Idle(Instant::now() - Duration::from_secs(1500), Duration::ZERO);
+        logic.set_resumed();
+        logic.status = Status::Idle(Instant::now(), Duration::ZERO);
+        assert!(logic.run_on_state().is_ok());
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .ends_with("Burnout risk 0/100 (low)"));
+
+        logic.args.risk_tooltip = false;
+        assert!(logic.run_on_state().is_ok());
+        assert!(!logic.waybar_output.tooltip.contains("Burnout risk"));
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let args = Args {
+            max_active_sessions: 0,
+            ..Default::default()
+        };
+        assert!(matches!(Logic::new(&args), Err(LogicError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_degraded() {
+        let args = Args {
+            idle_timeout: 600,
+            ..Default::default()
+        };
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        logic.set_degraded(Some("Lost the Wayland compositor connection".into()));
+        assert!(logic.run().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_ERROR);
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .starts_with("Idle detection unavailable: Lost the Wayland compositor connection\n"));
+
+        logic.set_degraded(None);
+        assert!(logic.run().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_OK);
+        assert!(!logic.waybar_output.tooltip.contains("unavailable"));
+    }
+
+    #[test]
+    fn test_overtime() {
+        let config = WorkingHoursConfig {
+            grace: Some(0),
+            ..WorkingHoursConfig::default()
+        };
+        let args = Args {
+            idle_timeout: 600,
+            waybar: true,
+            working_hours: Some(WorkingHours::new(&config)),
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+
+        // no working hours at all, any activity is overtime
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(60));
+        assert!(logic.run_on_state().is_ok());
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_OVERTIME);
+        assert!(logic
+            .waybar_output
+            .tooltip
+            .ends_with("outside the working hours"));
+
+        // the critical eyes still win
+        logic.status = Status::Active(Instant::now(), Duration::from_secs(1800));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
+
+        logic.status = Status::Idle(Instant::now(), Duration::ZERO);
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_OK);
+    }
+
+    #[test]
+    fn test_reload_keeps_the_current_session() {
+        let args = Args {
+            debug: false,
+            idle_timeout: 60,
+            waybar: true,
+            max_active_sessions: 3,
+            no_notify: false,
+            icon: "O".to_owned(),
+            ..Default::default()
+        };
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        let start = Instant::now();
+
+        logic.status = Status::Active(start, Duration::from_secs(240));
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.alert.counter_sent, 1);
+        assert_eq!(logic.waybar_output.class, STATUS_CRITICAL);
+
+        let args = Args {
+            max_active_sessions: 5,
+            ..args
+        };
+        logic.reload(&args);
+        assert!(matches!(logic.status, Status::Active(s, _) if s == start));
+        assert!(logic.alert.quiet);
+        assert_eq!(logic.alert.counter_sent, 1);
+        assert_eq!(logic.alert.next_send_time, Duration::from_secs(360));
+
+        assert!(logic.run_on_state().is_ok());
+        assert_eq!(logic.waybar_output.class, STATUS_WARNING);
+        assert_eq!(logic.waybar_output.text, "O O O O");
+    }
+
+    #[test]
+    fn test_transitions_are_recorded_in_the_history() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        logic.history = Some(History::new(dir.path()));
+
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(300), Duration::ZERO);
+        logic.alert.counter_sent = 2;
+        logic.handle_event(IdleEvent::Idle);
+        logic.handle_event(IdleEvent::Break);
+        logic.handle_event(IdleEvent::Resumed);
+
+        let records = logic.history.as_ref().unwrap().read_all().unwrap();
+        let kinds: Vec<SessionKind> = records.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SessionKind::Active, SessionKind::Idle, SessionKind::Break]
+        );
+        assert_eq!(records[0].duration_secs, 300);
+        assert_eq!(records[0].notifications, 2);
+        assert_eq!(records[1].notifications, 0);
+        assert!(records[0].end <= records[1].start + time::Duration::SECOND);
+    }
+
+    #[test]
+    fn test_repeated_events_are_ignored() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.alert.quiet = true;
+        logic.history = Some(History::new(dir.path()));
+
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(300), Duration::ZERO);
+        logic.handle_event(IdleEvent::Idle);
+        logic.status = Status::Idle(Instant::now() - Duration::from_secs(120), Duration::ZERO);
+        // the idle source and the logind IdleHint both report it
+        logic.handle_event(IdleEvent::Idle);
+        assert!(
+            matches!(logic.status, Status::Idle(start, _) if start.elapsed() >= Duration::from_secs(120))
+        );
+
+        logic.handle_event(IdleEvent::Break);
+        logic.status = Status::Break(Instant::now() - Duration::from_secs(60), Duration::ZERO);
+        // a suspend followed by a lock
+        logic.handle_event(IdleEvent::Break);
+        assert!(
+            matches!(logic.status, Status::Break(start, _) if start.elapsed() >= Duration::from_secs(60))
+        );
+
+        let records = logic.history.as_ref().unwrap().read_all().unwrap();
+        let kinds: Vec<SessionKind> = records.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, vec![SessionKind::Active, SessionKind::Idle]);
+        assert_eq!(logic.breaks, 1);
+    }
+
+    #[test]
+    fn test_resume_from_a_recent_checkpoint() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.status = Status::Active(
+            Instant::now() - Duration::from_secs(3 * 3600),
+            Duration::ZERO,
+        );
+        logic.alert.counter_sent = 7;
+        logic.alert.next_send_time = Duration::from_secs(3 * 3600 + 15);
+        let checkpoint = logic.checkpoint();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.history = Some(History::new(dir.path()));
+        assert!(logic.resume(checkpoint));
+        assert!(
+            matches!(logic.status, Status::Active(start, _) if start.elapsed() >= Duration::from_secs(3 * 3600))
+        );
+        assert_eq!(logic.alert.counter_sent, 7);
+        assert_eq!(
+            logic.alert.next_send_time,
+            Duration::from_secs(3 * 3600 + 15)
+        );
+        assert!(logic.history.unwrap().read_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_old_checkpoint_is_recorded_and_discarded() {
+        let args = Args {
+            idle_timeout: 60,
+            waybar: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(600), Duration::ZERO);
+        logic.alert.counter_sent = 3;
+        let mut checkpoint = logic.checkpoint();
+        checkpoint.start -= time::Duration::minutes(2);
+        checkpoint.saved_at -= time::Duration::minutes(2);
+
+        let mut logic = Logic::new(&args).unwrap();
+        logic.history = Some(History::new(dir.path()));
+        assert!(!logic.resume(checkpoint));
+        assert!(
+            matches!(logic.status, Status::Active(start, _) if start.elapsed() < Duration::from_secs(60))
+        );
+        assert_eq!(logic.alert.counter_sent, 0);
+
+        let records = logic.history.unwrap().read_all().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, SessionKind::Active);
+        assert_eq!(records[0].duration_secs, 600);
+        assert_eq!(records[0].notifications, 3);
+    }
+
     #[test]
     fn test_notifications_with_notification_disabled() {
         let args = Args {
@@ -547,13 +1953,14 @@ mod tests {
             max_active_sessions: 3,
             no_notify: true,
             icon: "O".to_owned(),
+            ..Default::default()
         };
         let start = Instant::now();
         let mut logic = Logic::new(&args).unwrap();
         logic.alert.quiet = true;
 
         logic.status = Status::Active(start, Duration::from_secs(270));
-        assert_eq!(logic.run_on_state().is_ok(), true);
+        assert!(logic.run_on_state().is_ok());
         assert_eq!(logic.alert.counter_sent, 0);
     }
 }
@@ -0,0 +1,300 @@
+use std::time::{Duration, Instant};
+use time::{OffsetDateTime, UtcOffset, Weekday};
+
+use crate::config::{TimeRange, WorkingHoursConfig};
+use crate::helpers::hhmm;
+
+/// Working hours of each weekday, the activity outside them is overtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingHours {
+    /// From monday to sunday, none for a day off
+    pub days: [Option<TimeRange>; 7],
+    /// Overtime before the first notification
+    pub grace: Duration,
+    /// Overtime between two notifications
+    pub repeat: Duration,
+    /// Time away ending the overtime stretch
+    pub rest: Duration,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: bool,
+}
+
+impl WorkingHours {
+    pub fn new(config: &WorkingHoursConfig) -> WorkingHours {
+        let day = |day: Option<TimeRange>, weekday: bool| {
+            day.or(if weekday { config.weekdays } else { None })
+        };
+
+        WorkingHours {
+            days: [
+                day(config.monday, true),
+                day(config.tuesday, true),
+                day(config.wednesday, true),
+                day(config.thursday, true),
+                day(config.friday, true),
+                day(config.saturday, false),
+                day(config.sunday, false),
+            ],
+            grace: Duration::from_secs(config.grace.unwrap_or(15 * 60)),
+            repeat: Duration::from_secs(config.repeat.unwrap_or(30 * 60)),
+            rest: Duration::from_secs(config.rest.unwrap_or(60 * 60)),
+            notify: config.notify.unwrap_or(true),
+        }
+    }
+
+    fn day(&self, weekday: Weekday) -> Option<TimeRange> {
+        self.days[weekday.number_days_from_monday() as usize]
+    }
+
+    /// Tell if `now`, a local time, is during the working hours
+    pub fn is_working(&self, now: OffsetDateTime) -> bool {
+        let time = now.time();
+        let today = self.day(now.weekday()).is_some_and(|range| {
+            range.start <= time && (time < range.end || crosses_midnight(range))
+        });
+        // the end of a range started yesterday
+        let yesterday = self
+            .day(now.weekday().previous())
+            .is_some_and(|range| crosses_midnight(range) && time < range.end);
+
+        today || yesterday
+    }
+
+    /// Seconds of `[start, end]` outside the working hours of the `offset` timezone
+    pub fn overtime_secs(
+        &self,
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+        offset: UtcOffset,
+    ) -> u64 {
+        let (start, end) = (start.to_offset(offset), end.to_offset(offset));
+        let mut working = 0;
+        // from the day before, its range may end after midnight
+        let mut date = start.date().previous_day().unwrap_or(start.date());
+        while date <= end.date() {
+            let Some(next) = date.next_day() else {
+                break;
+            };
+            if let Some(range) = self.day(date.weekday()) {
+                let from = date.with_time(range.start).assume_offset(offset);
+                let to = if crosses_midnight(range) { next } else { date };
+                let to = to.with_time(range.end).assume_offset(offset);
+                working += (end.min(to) - start.max(from)).whole_seconds().max(0);
+            }
+            date = next;
+        }
+
+        ((end - start).whole_seconds() - working).max(0) as u64
+    }
+}
+
+/// Tell if the range ends on the next day
+fn crosses_midnight(range: TimeRange) -> bool {
+    range.end < range.start
+}
+
+/// Active time outside the working hours, until the working hours start again or a long enough rest
+#[derive(Debug, Clone)]
+pub struct Overtime {
+    pub hours: WorkingHours,
+    /// Active time of the current overtime stretch
+    pub active: Duration,
+    /// Overtime of the next notification
+    next_notification: Duration,
+    /// Away since then, while outside the working hours
+    away_since: Option<Instant>,
+    /// Last update
+    last: Instant,
+}
+
+impl Overtime {
+    pub fn new(hours: WorkingHours, now: Instant) -> Overtime {
+        Overtime {
+            next_notification: hours.grace,
+            hours,
+            active: Duration::ZERO,
+            away_since: None,
+            last: now,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active = Duration::ZERO;
+        self.next_notification = self.hours.grace;
+        self.away_since = None;
+    }
+
+    /// Count the time since the last update, `local` is the wall clock,
+    /// return the overtime to notify
+    pub fn update(
+        &mut self,
+        now: Instant,
+        local: OffsetDateTime,
+        active: bool,
+    ) -> Option<Duration> {
+        let spent = now.saturating_duration_since(self.last);
+        let was_active = self.away_since.is_none();
+        self.last = now;
+        if self.hours.is_working(local) {
+            self.reset();
+            return None;
+        }
+        if was_active {
+            self.active += spent;
+        }
+        match (active, self.away_since) {
+            (false, None) => self.away_since = Some(now),
+            (true, Some(since)) => {
+                self.away_since = None;
+                if now.saturating_duration_since(since) >= self.hours.rest {
+                    self.reset();
+                }
+            }
+            _ => {}
+        }
+        if !active || self.active < self.next_notification {
+            return None;
+        }
+        self.next_notification = self.active + self.hours.repeat;
+
+        Some(self.active)
+    }
+
+    /// Overtime worth showing, while active after hours
+    pub fn is_overtime(&self) -> bool {
+        !self.active.is_zero() && self.away_since.is_none()
+    }
+
+    /// Tooltip line
+    pub fn describe(&self) -> Option<String> {
+        if self.active.is_zero() {
+            return None;
+        }
+        Some(format!(
+            "Active for {} outside the working hours",
+            hhmm(self.active)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{datetime, offset, time};
+
+    fn hours() -> WorkingHours {
+        WorkingHours::new(&WorkingHoursConfig {
+            weekdays: Some(TimeRange {
+                start: time!(09:00),
+                end: time!(18:00),
+            }),
+            friday: Some(TimeRange {
+                start: time!(09:00),
+                end: time!(12:00),
+            }),
+            ..WorkingHoursConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_working_hours() {
+        let hours = hours();
+        // a monday
+        assert!(hours.is_working(datetime!(2026-03-09 09:00 +1)));
+        assert!(!hours.is_working(datetime!(2026-03-09 18:00 +1)));
+        assert!(!hours.is_working(datetime!(2026-03-13 14:00 +1)));
+        assert!(!hours.is_working(datetime!(2026-03-14 10:00 +1)));
+
+        // 17:00 to 19:00 local, then the night until 10:00 on tuesday
+        let offset = offset!(+1);
+        assert_eq!(
+            hours.overtime_secs(
+                datetime!(2026-03-09 16:00 UTC),
+                datetime!(2026-03-09 18:00 UTC),
+                offset
+            ),
+            3600
+        );
+        assert_eq!(
+            hours.overtime_secs(
+                datetime!(2026-03-09 20:00 +1),
+                datetime!(2026-03-10 10:00 +1),
+                offset
+            ),
+            13 * 3600
+        );
+    }
+
+    #[test]
+    fn test_night_shift() {
+        let hours = WorkingHours::new(&WorkingHoursConfig {
+            monday: Some(TimeRange {
+                start: time!(22:00),
+                end: time!(06:00),
+            }),
+            ..WorkingHoursConfig::default()
+        });
+        assert!(!hours.is_working(datetime!(2026-03-09 21:00 +1)));
+        assert!(hours.is_working(datetime!(2026-03-09 23:00 +1)));
+        // tuesday morning, still the shift of monday
+        assert!(hours.is_working(datetime!(2026-03-10 05:00 +1)));
+        assert!(!hours.is_working(datetime!(2026-03-10 06:00 +1)));
+        assert!(!hours.is_working(datetime!(2026-03-10 23:00 +1)));
+
+        // 21:00 to 22:00, then 06:00 to 07:00
+        assert_eq!(
+            hours.overtime_secs(
+                datetime!(2026-03-09 21:00 +1),
+                datetime!(2026-03-10 07:00 +1),
+                offset!(+1)
+            ),
+            2 * 3600
+        );
+        assert_eq!(
+            hours.overtime_secs(
+                datetime!(2026-03-10 04:00 +1),
+                datetime!(2026-03-10 07:00 +1),
+                offset!(+1)
+            ),
+            3600
+        );
+    }
+
+    #[test]
+    fn test_overtime_stretch() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let evening = datetime!(2026-03-09 22:00 UTC);
+        let mut overtime = Overtime::new(hours(), start);
+
+        assert_eq!(overtime.update(minutes(10), evening, true), None);
+        assert!(overtime.is_overtime());
+        assert_eq!(
+            overtime.update(minutes(15), evening, true),
+            Some(Duration::from_secs(15 * 60))
+        );
+        assert_eq!(overtime.update(minutes(30), evening, true), None);
+        // a short idle period doesn't end the stretch
+        assert_eq!(overtime.update(minutes(40), evening, false), None);
+        assert!(!overtime.is_overtime());
+        assert_eq!(overtime.update(minutes(50), evening, true), None);
+        assert_eq!(
+            overtime.update(minutes(55), evening, true),
+            Some(Duration::from_secs(45 * 60))
+        );
+
+        // a long rest does
+        overtime.update(minutes(56), evening, false);
+        overtime.update(minutes(120), evening, true);
+        assert_eq!(overtime.describe(), None);
+        overtime.update(minutes(121), evening, true);
+        assert_eq!(
+            overtime.describe().unwrap(),
+            "Active for 00:01 outside the working hours"
+        );
+
+        // so does the next working day
+        overtime.update(minutes(122), datetime!(2026-03-10 09:30 UTC), true);
+        assert_eq!(overtime.describe(), None);
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::PomodoroConfig;
+use crate::helpers::{next_countdown_minute, round_up_minute};
+use crate::logic::schedule::Level;
+
+/// Pomodoro settings, the config values completed with the classic durations
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroSettings {
+    /// Work interval
+    pub work: Duration,
+    /// Break after a work interval
+    pub short_break: Duration,
+    /// Break after `long_break_after` work intervals
+    pub long_break: Duration,
+    pub long_break_after: u32,
+    /// Added to a break cut short by activity, which otherwise ends it
+    pub extend: Option<Duration>,
+}
+
+impl From<&PomodoroConfig> for PomodoroSettings {
+    fn from(config: &PomodoroConfig) -> Self {
+        PomodoroSettings {
+            work: Duration::from_secs(config.work.unwrap_or(25 * 60)),
+            short_break: Duration::from_secs(config.short_break.unwrap_or(5 * 60)),
+            long_break: Duration::from_secs(config.long_break.unwrap_or(15 * 60)),
+            long_break_after: config.long_break_after.unwrap_or(4),
+            extend: config.extend.map(Duration::from_secs),
+        }
+    }
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings::from(&PomodoroConfig::default())
+    }
+}
+
+/// Pomodoro phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn icon(&self) -> &'static str {
+        match self {
+            Phase::Work => "🍅",
+            Phase::ShortBreak => "☕",
+            Phase::LongBreak => "🌴",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Work => write!(f, "work"),
+            Phase::ShortBreak => write!(f, "short break"),
+            Phase::LongBreak => write!(f, "long break"),
+        }
+    }
+}
+
+/// Phase changes worth a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroEvent {
+    /// The work interval is over
+    BreakDue(Phase),
+    /// The user stayed away long enough
+    BreakDone,
+    /// The user came back before the end of the break
+    CutShort { extended: bool },
+}
+
+/// Pomodoro cycle where the work only counts while active and the breaks only while away
+#[derive(Debug, Clone)]
+pub struct Pomodoro {
+    pub settings: PomodoroSettings,
+    phase: Phase,
+    /// Time worked, or away, in the current phase
+    done: Duration,
+    /// Time needed to end the current phase
+    needed: Duration,
+    /// Time still active while the break is due
+    skipped: Duration,
+    /// Work intervals completed since the last long break
+    completed: u32,
+    /// Number of breaks cut short by activity
+    pub cut_short: u32,
+    /// The current or last break was cut short
+    flagged: bool,
+    /// Last update
+    last: Instant,
+}
+
+/// Countdown in minutes, rounded up
+fn clock(duration: Duration) -> String {
+    format!("{} min", round_up_minute(duration).as_secs() / 60)
+}
+
+impl Pomodoro {
+    pub fn new(settings: PomodoroSettings, now: Instant) -> Pomodoro {
+        Pomodoro {
+            needed: settings.work,
+            settings,
+            phase: Phase::Work,
+            done: Duration::ZERO,
+            skipped: Duration::ZERO,
+            completed: 0,
+            cut_short: 0,
+            flagged: false,
+            last: now,
+        }
+    }
+
+    /// Time left in the current phase
+    pub fn remaining(&self) -> Duration {
+        self.needed.saturating_sub(self.done)
+    }
+
+    fn start(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.needed = match phase {
+            Phase::Work => self.settings.work,
+            Phase::ShortBreak => self.settings.short_break,
+            Phase::LongBreak => self.settings.long_break,
+        };
+        self.done = Duration::ZERO;
+        self.skipped = Duration::ZERO;
+    }
+
+    /// Count the time since the last update, `away` tells if the user was idle or on a break
+    pub fn update(&mut self, now: Instant, away: bool) -> Option<PomodoroEvent> {
+        let spent = now.saturating_duration_since(self.last);
+        self.last = now;
+        match (self.phase, away) {
+            (Phase::Work, false) => self.done += spent,
+            (Phase::Work, true) => {}
+            (_, true) => self.done += spent,
+            (_, false) => self.skipped += spent,
+        }
+        if self.done < self.needed {
+            return None;
+        }
+
+        let event = match self.phase {
+            Phase::Work => {
+                self.completed += 1;
+                let phase = if self.completed >= self.settings.long_break_after {
+                    self.completed = 0;
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                };
+                self.flagged = false;
+                self.start(phase);
+                PomodoroEvent::BreakDue(phase)
+            }
+            Phase::ShortBreak | Phase::LongBreak => {
+                self.flagged = false;
+                self.start(Phase::Work);
+                PomodoroEvent::BreakDone
+            }
+        };
+
+        Some(event)
+    }
+
+    /// Time before the phase or the level changes, none while nothing is counted
+    pub fn next_change(&self, away: bool) -> Option<Duration> {
+        match (self.phase, away) {
+            (Phase::Work, true) => None,
+            (Phase::Work, false) | (_, true) => Some(self.remaining()),
+            (_, false) => Some(self.needed.saturating_sub(self.skipped)).filter(|d| !d.is_zero()),
+        }
+    }
+
+    /// Time before the countdown shows one minute less, none while it doesn't move
+    pub fn next_clock_change(&self, away: bool) -> Option<Duration> {
+        let counting = (self.phase == Phase::Work) != away;
+        counting.then(|| next_countdown_minute(self.remaining()))
+    }
+
+    /// Don't count the time since the last update, while paused
+    pub fn skip(&mut self, now: Instant) {
+        self.last = now;
+    }
+
+    /// The user went idle, `detection` ago
+    pub fn went_away(&mut self, detection: Duration) {
+        if self.phase != Phase::Work {
+            self.done += detection;
+        }
+    }
+
+    /// The user is active again, a started break is cut short
+    pub fn came_back(&mut self) -> Option<PomodoroEvent> {
+        if self.phase == Phase::Work || self.done.is_zero() || self.done >= self.needed {
+            return None;
+        }
+        self.cut_short += 1;
+        self.flagged = true;
+        match self.settings.extend {
+            Some(extend) => {
+                self.needed += extend;
+                Some(PomodoroEvent::CutShort { extended: true })
+            }
+            None => {
+                self.start(Phase::Work);
+                Some(PomodoroEvent::CutShort { extended: false })
+            }
+        }
+    }
+
+    /// Warning while a break is due or after a cut short one,
+    /// critical when still active a whole break after it was due
+    pub fn level(&self) -> Level {
+        match self.phase {
+            Phase::Work if self.flagged => Level::Warning,
+            Phase::Work => Level::Ok,
+            _ if self.skipped >= self.needed => Level::Critical,
+            _ => Level::Warning,
+        }
+    }
+
+    /// Waybar text, the phase and the time left
+    pub fn text(&self) -> String {
+        format!("{} {}", self.phase.icon(), clock(self.remaining()))
+    }
+
+    /// Tooltip lines
+    pub fn describe(&self) -> String {
+        let mut description = match self.phase {
+            Phase::Work => format!(
+                "Pomodoro {}/{}, {} of work left",
+                self.completed + 1,
+                self.settings.long_break_after,
+                clock(self.remaining())
+            ),
+            phase => format!(
+                "Pomodoro {phase}, step away for {}",
+                clock(self.remaining())
+            ),
+        };
+        if self.flagged {
+            description.push_str("\nThe break was cut short");
+        }
+        if self.cut_short > 0 {
+            description.push_str(&format!("\n{} break(s) cut short", self.cut_short));
+        }
+
+        description
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(extend: Option<u64>) -> PomodoroSettings {
+        PomodoroSettings::from(&PomodoroConfig {
+            long_break_after: Some(2),
+            extend,
+            ..PomodoroConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_cycle_counts_only_the_real_work_and_breaks() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut pomodoro = Pomodoro::new(settings(None), start);
+        assert_eq!(pomodoro.text(), "🍅 25 min");
+
+        assert_eq!(pomodoro.update(minutes(10), false), None);
+        // idle time isn't work
+        assert_eq!(pomodoro.update(minutes(20), true), None);
+        assert_eq!(pomodoro.text(), "🍅 15 min");
+        assert_eq!(
+            pomodoro.update(minutes(35), false),
+            Some(PomodoroEvent::BreakDue(Phase::ShortBreak))
+        );
+        assert_eq!(pomodoro.level(), Level::Warning);
+
+        // still working, the break doesn't count
+        assert_eq!(
+            pomodoro.next_change(false),
+            Some(Duration::from_secs(5 * 60))
+        );
+        assert_eq!(pomodoro.update(minutes(41), false), None);
+        assert_eq!(pomodoro.next_change(false), None);
+        assert_eq!(pomodoro.text(), "☕ 5 min");
+        assert_eq!(pomodoro.level(), Level::Critical);
+        pomodoro.went_away(Duration::from_secs(60));
+        assert_eq!(pomodoro.update(minutes(44), true), None);
+        assert_eq!(pomodoro.text(), "☕ 1 min");
+        assert_eq!(
+            pomodoro.update(minutes(45), true),
+            Some(PomodoroEvent::BreakDone)
+        );
+        assert_eq!(pomodoro.came_back(), None);
+
+        assert_eq!(
+            pomodoro.update(minutes(71), false),
+            Some(PomodoroEvent::BreakDue(Phase::LongBreak))
+        );
+        assert_eq!(pomodoro.text(), "🌴 15 min");
+    }
+
+    #[test]
+    fn test_break_cut_short() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut pomodoro = Pomodoro::new(settings(None), start);
+        pomodoro.update(minutes(25), false);
+        pomodoro.update(minutes(27), true);
+
+        assert_eq!(
+            pomodoro.came_back(),
+            Some(PomodoroEvent::CutShort { extended: false })
+        );
+        assert_eq!(pomodoro.phase, Phase::Work);
+        assert_eq!(pomodoro.level(), Level::Warning);
+        assert!(pomodoro.describe().contains("1 break(s) cut short"));
+
+        let mut pomodoro = Pomodoro::new(settings(Some(120)), start);
+        pomodoro.update(minutes(25), false);
+        pomodoro.update(minutes(27), true);
+        assert_eq!(
+            pomodoro.came_back(),
+            Some(PomodoroEvent::CutShort { extended: true })
+        );
+        assert_eq!(pomodoro.phase, Phase::ShortBreak);
+        assert_eq!(pomodoro.text(), "☕ 5 min");
+    }
+}
@@ -0,0 +1,247 @@
+use notify_rust::Urgency;
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::ScheduleConfig;
+use crate::exercises::Category;
+use crate::helpers::{hhmm, round_up_minute};
+
+/// Kind of break schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleKind {
+    /// Short and frequent, like the 20-20-20 rule for the eyes
+    Micro,
+    /// Long and rare, to leave the desk
+    Long,
+}
+
+impl fmt::Display for ScheduleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleKind::Micro => write!(f, "micro break"),
+            ScheduleKind::Long => write!(f, "long break"),
+        }
+    }
+}
+
+/// Urgency of the schedule notifications
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleUrgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl From<ScheduleUrgency> for Urgency {
+    fn from(urgency: ScheduleUrgency) -> Self {
+        match urgency {
+            ScheduleUrgency::Low => Urgency::Low,
+            ScheduleUrgency::Normal => Urgency::Normal,
+            ScheduleUrgency::Critical => Urgency::Critical,
+        }
+    }
+}
+
+/// How urgent a break is, the waybar class follows the most urgent one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// Schedule settings, the config values completed with the defaults of the kind
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleSettings {
+    pub kind: ScheduleKind,
+    /// Work time between two breaks
+    pub interval: Duration,
+    /// Time away needed for the break to count
+    pub duration: Duration,
+    /// Categories of the suggested exercises
+    pub categories: Vec<Category>,
+    /// Notification urgency when the break is due, overdue ones are critical
+    pub urgency: Urgency,
+    /// Send notifications, otherwise only the waybar class changes
+    pub notify: bool,
+}
+
+impl ScheduleSettings {
+    pub fn new(kind: ScheduleKind, config: &ScheduleConfig) -> ScheduleSettings {
+        let (interval, duration, category, urgency) = match kind {
+            ScheduleKind::Micro => (20 * 60, 20, Category::Eyes, Urgency::Low),
+            ScheduleKind::Long => (60 * 60, 10 * 60, Category::Stretching, Urgency::Normal),
+        };
+
+        ScheduleSettings {
+            kind,
+            interval: Duration::from_secs(config.interval.unwrap_or(interval)),
+            duration: Duration::from_secs(config.duration.unwrap_or(duration)),
+            categories: config.categories.clone().unwrap_or(vec![category]),
+            urgency: config.urgency.map(Urgency::from).unwrap_or(urgency),
+            notify: config.notify.unwrap_or(true),
+        }
+    }
+}
+
+/// Break schedule, due every `interval` of work not interrupted by a long enough break
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub settings: ScheduleSettings,
+    /// End of the last break long enough, or the daemon start
+    since: Instant,
+    /// Highest level notified since the last break
+    notified: Level,
+}
+
+impl Schedule {
+    pub fn new(settings: ScheduleSettings, since: Instant) -> Schedule {
+        Schedule {
+            settings,
+            since,
+            notified: Level::Ok,
+        }
+    }
+
+    /// Work time since the last break
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.since)
+    }
+
+    /// Due after `interval`, overdue after one and a half
+    pub fn level(&self, now: Instant) -> Level {
+        let elapsed = self.elapsed(now);
+        if elapsed >= self.settings.interval * 3 / 2 {
+            Level::Critical
+        } else if elapsed >= self.settings.interval {
+            Level::Warning
+        } else {
+            Level::Ok
+        }
+    }
+
+    /// Time before the break is due or overdue, none once overdue
+    pub fn next_change(&self, now: Instant) -> Option<Duration> {
+        let elapsed = self.elapsed(now);
+        [self.settings.interval, self.settings.interval * 3 / 2]
+            .into_iter()
+            .find(|threshold| *threshold > elapsed)
+            .map(|threshold| threshold - elapsed)
+    }
+
+    /// A break of `away` ended at `now`, it counts if long enough
+    pub fn end_break(&mut self, away: Duration, now: Instant) {
+        if away >= self.settings.duration {
+            self.restart(now);
+        }
+    }
+
+    pub fn restart(&mut self, now: Instant) {
+        self.since = now;
+        self.notified = Level::Ok;
+    }
+
+    /// Level to notify, once when the break is due and once when overdue
+    pub fn next_notification(&mut self, now: Instant) -> Option<Level> {
+        let level = self.level(now);
+        if level <= self.notified {
+            return None;
+        }
+        self.notified = level;
+
+        Some(level)
+    }
+
+    /// Tooltip line
+    pub fn describe(&self, now: Instant) -> String {
+        let elapsed = self.elapsed(now);
+        match self.settings.interval.checked_sub(elapsed) {
+            Some(left) if !left.is_zero() => {
+                format!(
+                    "Next {} in {}",
+                    self.settings.kind,
+                    hhmm(round_up_minute(left))
+                )
+            }
+            _ => format!(
+                "A {} is due for {}",
+                self.settings.kind,
+                hhmm(elapsed - self.settings.interval)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_defaults() {
+        let micro = ScheduleSettings::new(ScheduleKind::Micro, &ScheduleConfig::default());
+        assert_eq!(micro.interval, Duration::from_secs(1200));
+        assert_eq!(micro.duration, Duration::from_secs(20));
+        assert_eq!(micro.categories, vec![Category::Eyes]);
+
+        let config = ScheduleConfig {
+            interval: Some(5400),
+            categories: Some(vec![Category::Stretching, Category::Mental]),
+            urgency: Some(ScheduleUrgency::Critical),
+            ..ScheduleConfig::default()
+        };
+        let long = ScheduleSettings::new(ScheduleKind::Long, &config);
+        assert_eq!(long.interval, Duration::from_secs(5400));
+        assert_eq!(long.duration, Duration::from_secs(600));
+        assert_eq!(long.urgency, Urgency::Critical);
+        assert!(long.notify);
+    }
+
+    #[test]
+    fn test_schedule_levels_and_breaks() {
+        let start = Instant::now();
+        let settings = ScheduleSettings::new(ScheduleKind::Micro, &ScheduleConfig::default());
+        let mut schedule = Schedule::new(settings, start);
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+
+        assert_eq!(schedule.level(minutes(19)), Level::Ok);
+        assert_eq!(schedule.next_notification(minutes(19)), None);
+        assert_eq!(
+            schedule.next_change(minutes(19)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            schedule.next_change(minutes(20)),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(schedule.next_change(minutes(30)), None);
+        assert_eq!(schedule.describe(minutes(15)), "Next micro break in 00:05");
+
+        assert_eq!(
+            schedule.next_notification(minutes(20)),
+            Some(Level::Warning)
+        );
+        assert_eq!(schedule.next_notification(minutes(25)), None);
+        assert_eq!(
+            schedule.describe(minutes(25)),
+            "A micro break is due for 00:05"
+        );
+        assert_eq!(
+            schedule.next_notification(minutes(30)),
+            Some(Level::Critical)
+        );
+        assert_eq!(schedule.next_notification(minutes(40)), None);
+
+        // too short to count
+        schedule.end_break(Duration::from_secs(10), minutes(40));
+        assert_eq!(schedule.level(minutes(40)), Level::Critical);
+
+        schedule.end_break(Duration::from_secs(30), minutes(41));
+        assert_eq!(schedule.level(minutes(41)), Level::Ok);
+        assert_eq!(
+            schedule.next_notification(minutes(61)),
+            Some(Level::Warning)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::error::Error;
+use std::os::fd::BorrowedFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use zbus::blocking::Connection;
+use zbus::dbus_proxy;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::helpers::channel::{self, Receiver, Sender};
+use crate::source::{IdleEvent, IdleSource};
+
+#[dbus_proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[dbus_proxy(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    #[dbus_proxy(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+
+    #[dbus_proxy(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+
+    #[dbus_proxy(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+
+    #[dbus_proxy(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+}
+
+/// systemd-logind source, reporting suspend and lock as breaks
+#[derive(Debug)]
+pub struct LogindSource {
+    /// Bus where logind lives
+    conn: Connection,
+    /// Events sent by the listener threads
+    receiver: Option<Receiver<IdleEvent>>,
+}
+
+impl LogindSource {
+    pub fn new() -> Result<LogindSource, Box<dyn Error>> {
+        Ok(LogindSource::with_connection(Connection::system()?))
+    }
+
+    pub fn with_connection(conn: Connection) -> LogindSource {
+        LogindSource {
+            conn,
+            receiver: None,
+        }
+    }
+}
+
+/// Forward each item of a signal iterator as an idle event
+fn forward<I, F>(iter: I, sender: Sender<IdleEvent>, mut to_event: F)
+where
+    I: Iterator + Send + 'static,
+    F: FnMut(I::Item) -> Option<IdleEvent> + Send + 'static,
+{
+    thread::spawn(move || {
+        for item in iter {
+            if let Some(event) = to_event(item) {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+impl IdleSource for LogindSource {
+    fn name(&self) -> &'static str {
+        "logind"
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        let manager = ManagerProxyBlocking::new(&self.conn)?;
+        let session_path = manager.get_session("auto")?;
+        let session = SessionProxyBlocking::builder(&self.conn)
+            .path(session_path)?
+            .build()?;
+        let (sender, receiver) = channel::channel();
+        let locked_hint = session.locked_hint().unwrap_or(false);
+        let locked = Arc::new(AtomicBool::new(locked_hint));
+        // started on the lock screen, the break lasts until the unlock
+        if locked_hint {
+            sender.send(IdleEvent::Locked)?;
+        }
+
+        // waking up usually lands on the lock screen, only the unlock or the user input end the break
+        forward(
+            manager.receive_prepare_for_sleep()?,
+            sender.clone(),
+            |signal| signal.args().ok()?.start.then_some(IdleEvent::Break),
+        );
+        let is_locked = Arc::clone(&locked);
+        forward(session.receive_lock()?, sender.clone(), move |_| {
+            is_locked.store(true, Ordering::Relaxed);
+            Some(IdleEvent::Locked)
+        });
+        let is_locked = Arc::clone(&locked);
+        forward(session.receive_unlock()?, sender.clone(), move |_| {
+            is_locked.store(false, Ordering::Relaxed);
+            Some(IdleEvent::Unlocked)
+        });
+        // the property stream starts with the current value, only report changes
+        let mut idle_hint = session.idle_hint()?;
+        forward(
+            session.receive_idle_hint_changed(),
+            sender,
+            move |changed| {
+                let value = changed.get().ok()?;
+                if value == idle_hint {
+                    return None;
+                }
+                idle_hint = value;
+                // the hint flips on the lock screen too
+                if locked.load(Ordering::Relaxed) {
+                    return None;
+                }
+                Some(if value {
+                    IdleEvent::Idle
+                } else {
+                    IdleEvent::Resumed
+                })
+            },
+        );
+        self.receiver = Some(receiver);
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>> {
+        Ok(self
+            .receiver
+            .as_ref()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default())
+    }
+
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.receiver.as_ref().and_then(Receiver::fd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::private_bus::PrivateBus;
+    use std::time::{Duration, Instant};
+    use zbus::{dbus_interface, SignalContext};
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_31";
+
+    struct MockManager;
+
+    #[dbus_interface(name = "org.freedesktop.login1.Manager")]
+    impl MockManager {
+        fn get_session(&self, _session_id: &str) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(SESSION_PATH).unwrap()
+        }
+
+        #[dbus_interface(signal)]
+        async fn prepare_for_sleep(ctxt: &SignalContext<'_>, start: bool) -> zbus::Result<()>;
+    }
+
+    struct MockSession {
+        idle_hint: bool,
+        locked_hint: bool,
+    }
+
+    #[dbus_interface(name = "org.freedesktop.login1.Session")]
+    impl MockSession {
+        #[dbus_interface(property)]
+        fn idle_hint(&self) -> bool {
+            self.idle_hint
+        }
+
+        #[dbus_interface(property)]
+        fn locked_hint(&self) -> bool {
+            self.locked_hint
+        }
+
+        #[dbus_interface(signal)]
+        async fn lock(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+        #[dbus_interface(signal)]
+        async fn unlock(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+    }
+
+    /// Dispatch until `count` events are received or a few seconds elapsed
+    fn wait_events(source: &mut LogindSource, count: usize) -> Vec<IdleEvent> {
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while events.len() < count && start.elapsed() < Duration::from_secs(5) {
+            events.extend(source.dispatch().unwrap());
+            thread::sleep(Duration::from_millis(20));
+        }
+        events
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored logind::`
+    #[test]
+    #[ignore]
+    fn test_logind_events_from_a_mocked_service() {
+        let bus = PrivateBus::start();
+        let service = bus.connect();
+        service
+            .object_server()
+            .at("/org/freedesktop/login1", MockManager)
+            .unwrap();
+        service
+            .object_server()
+            .at(
+                SESSION_PATH,
+                MockSession {
+                    idle_hint: false,
+                    locked_hint: false,
+                },
+            )
+            .unwrap();
+        service.request_name("org.freedesktop.login1").unwrap();
+
+        let mut source = LogindSource::with_connection(bus.connect());
+        source.init().unwrap();
+        // let the listener threads subscribe
+        thread::sleep(Duration::from_millis(200));
+
+        let manager = service
+            .object_server()
+            .interface::<_, MockManager>("/org/freedesktop/login1")
+            .unwrap();
+        zbus::block_on(MockManager::prepare_for_sleep(
+            manager.signal_context(),
+            true,
+        ))
+        .unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![IdleEvent::Break]);
+        zbus::block_on(MockManager::prepare_for_sleep(
+            manager.signal_context(),
+            false,
+        ))
+        .unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![]);
+
+        let session = service
+            .object_server()
+            .interface::<_, MockSession>(SESSION_PATH)
+            .unwrap();
+        zbus::block_on(MockSession::lock(session.signal_context())).unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![IdleEvent::Locked]);
+        // ignored on the lock screen
+        session.get_mut().idle_hint = true;
+        zbus::block_on(session.get().idle_hint_changed(session.signal_context())).unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![]);
+        zbus::block_on(MockSession::unlock(session.signal_context())).unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![IdleEvent::Unlocked]);
+
+        session.get_mut().idle_hint = false;
+        zbus::block_on(session.get().idle_hint_changed(session.signal_context())).unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![IdleEvent::Resumed]);
+        session.get_mut().idle_hint = true;
+        zbus::block_on(session.get().idle_hint_changed(session.signal_context())).unwrap();
+        assert_eq!(wait_events(&mut source, 1), vec![IdleEvent::Idle]);
+    }
+
+    /// Needs `dbus-daemon`, run with `cargo test -- --ignored logind::`
+    #[test]
+    #[ignore]
+    fn test_logind_starting_locked() {
+        let bus = PrivateBus::start();
+        let service = bus.connect();
+        service
+            .object_server()
+            .at("/org/freedesktop/login1", MockManager)
+            .unwrap();
+        service
+            .object_server()
+            .at(
+                SESSION_PATH,
+                MockSession {
+                    idle_hint: true,
+                    locked_hint: true,
+                },
+            )
+            .unwrap();
+        service.request_name("org.freedesktop.login1").unwrap();
+
+        let mut source = LogindSource::with_connection(bus.connect());
+        source.init().unwrap();
+        assert_eq!(source.dispatch().unwrap(), vec![IdleEvent::Locked]);
+    }
+}
@@ -1,9 +1,33 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::path::PathBuf;
 
+mod config;
+mod control;
+mod daemon;
+mod exercises;
 mod helpers;
+mod history;
 mod logic;
+mod logind;
+mod metrics;
+mod source;
+mod stats;
 mod wayland;
-use wayland::Wayland;
+mod x11;
+use config::{Classes, Stacking};
+use control::ControlCommand;
+use daemon::Daemon;
+use exercises::selector::{self, Selector};
+use exercises::{Catalog, Category, Exercise, ExercisesCommand};
+use history::History;
+use logic::budget::BudgetSettings;
+use logic::overtime::WorkingHours;
+use logic::pomodoro::PomodoroSettings;
+use logic::schedule::ScheduleSettings;
+use logic::{Logic, Mode};
+use source::SourceKind;
+use stats::StatsArgs;
+use wayland::SeatPolicy;
 
 /// Command line parameters
 #[derive(Parser, Debug, Clone)]
@@ -22,19 +46,165 @@ pub struct Args {
     #[arg(short, long, default_value_t = 3)]
     max_active_sessions: u64,
     /// Disable the notification
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, overrides_with = "notify")]
     no_notify: bool,
+    /// Enable the notification, over the configuration file
+    #[arg(long, default_value_t = false, overrides_with = "no_notify")]
+    notify: bool,
     /// Enable waybar module output
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, overrides_with = "no_waybar")]
     waybar: bool,
+    /// Disable waybar module output, over the configuration file
+    #[arg(long, default_value_t = false, overrides_with = "waybar")]
+    no_waybar: bool,
+    /// Watch the eyes, or cycle pomodoro work intervals and breaks
+    #[arg(long, value_enum, default_value_t = Mode::Eyes)]
+    mode: Mode,
+    /// Where the idle events come from
+    #[arg(long, value_enum, default_value_t = SourceKind::Auto)]
+    source: SourceKind,
+    /// Source used instead when the first one can't detect the idle time
+    #[arg(long, value_enum)]
+    fallback_source: Option<SourceKind>,
+    /// With several seats, whether any or all of them must be active to be active
+    #[arg(long, value_enum, default_value_t = SeatPolicy::AnyActive)]
+    seat_policy: SeatPolicy,
+    /// Listen to systemd-logind, suspend and lock count as a break
+    #[arg(long, default_value_t = false, overrides_with = "no_logind")]
+    logind: bool,
+    /// Don't listen to systemd-logind, over the configuration file
+    #[arg(long, default_value_t = false, overrides_with = "logind")]
+    no_logind: bool,
+    /// Don't record the sessions history
+    #[arg(long, default_value_t = false, overrides_with = "history")]
+    no_history: bool,
+    /// Record the sessions history, over the configuration file
+    #[arg(long, default_value_t = false, overrides_with = "no_history")]
+    history: bool,
+    /// Don't resume the session of the previous run
+    #[arg(long, default_value_t = false, overrides_with = "resume")]
+    no_resume: bool,
+    /// Resume the session of the previous run, over the configuration file
+    #[arg(long, default_value_t = false, overrides_with = "no_resume")]
+    resume: bool,
+    /// Write Prometheus metrics to this file, for the node_exporter textfile collector
+    #[arg(long)]
+    metrics_file: Option<PathBuf>,
+    /// Configuration file, default to ~/.config/burnout-detector/config.toml
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    /// Notification expiration in milliseconds, 0 never expires
+    #[arg(skip)]
+    notification_timeout: u32,
+    /// Update the displayed notification or stack a new one
+    #[arg(skip)]
+    stacking: Stacking,
+    /// Waybar class names
+    #[arg(skip)]
+    classes: Classes,
+    /// Keep the builtin exercises
+    #[arg(skip = true)]
+    builtin_exercises: bool,
+    /// User exercise catalog
+    #[arg(skip)]
+    exercises_catalog: Option<PathBuf>,
+    /// SafeEyes configuration to take the breaks from
+    #[arg(skip)]
+    exercises_safeeyes: Option<PathBuf>,
+    /// Exercises suggested in the notifications
+    #[arg(skip = Catalog::builtin().exercises)]
+    exercises: Vec<Exercise>,
+    /// Categories of the suggested exercises, all of them when empty
+    #[arg(skip)]
+    exercise_categories: Vec<Category>,
+    /// Micro and long break schedules
+    #[arg(skip)]
+    schedules: Vec<ScheduleSettings>,
+    /// Pomodoro mode durations
+    #[arg(skip)]
+    pomodoro: PomodoroSettings,
+    /// Daily active time budget
+    #[arg(skip)]
+    budget: Option<BudgetSettings>,
+    /// Working hours, the activity outside them is overtime
+    #[arg(skip)]
+    working_hours: Option<WorkingHours>,
+    /// Show the burnout risk in the waybar tooltip
+    #[arg(skip = true)]
+    risk_tooltip: bool,
+    /// Send a burnout risk summary once a week
+    #[arg(skip)]
+    weekly_summary: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Report the active time and the breaks from the history
+    Stats(StatsArgs),
+    /// List or validate the exercises
+    Exercises {
+        #[command(subcommand)]
+        command: ExercisesCommand,
+    },
+    /// Drive the running daemon
+    Ctl {
+        #[command(subcommand)]
+        command: ControlCommand,
+    },
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args::parse_from([env!("CARGO_PKG_NAME")])
+    }
 }
 
 fn main() {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = match config::load(&matches) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
     if args.debug {
         eprintln!("Params: {args:?}");
     }
 
+    match &args.command {
+        Some(Command::Stats(stats_args)) => {
+            if let Err(e) = stats::run(&args, stats_args) {
+                eprintln!("Can't compute the stats: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Exercises { command }) => {
+            if let Err(e) = exercises::run(&args.exercises, command) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Ctl { command }) => {
+            let reply = control::socket_path()
+                .ok_or_else(|| "XDG_RUNTIME_DIR is not set".into())
+                .and_then(|path| control::send(&path, command));
+            match reply {
+                Ok(reply) => println!("{reply}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+
     if !args.waybar && args.no_notify {
         eprintln!("You cannot disable waybar and notification in the same time");
         std::process::exit(1);
@@ -46,7 +216,21 @@ fn main() {
         eprintln!("Notification only mode");
     }
 
-    Wayland::new(&args)
-        .expect("Can't initialize the application")
-        .run();
+    let mut logic = match Logic::new(&args) {
+        Ok(logic) => logic,
+        Err(e) => logic::exit_with_error(&args, &format!("Can't initialize the logic core: {e}")),
+    };
+    if !args.no_history {
+        logic.history = History::open_default();
+        logic.restore_budget();
+        if let Some(path) = selector::default_path() {
+            logic.alert.selector = Selector::persistent(&path);
+        }
+    }
+    let sources = match source::from_args(&args) {
+        Ok(sources) => sources,
+        Err(e) => logic::exit_with_error(&args, &format!("Can't initialize the idle sources: {e}")),
+    };
+
+    Daemon::new(sources, logic, matches).run();
 }
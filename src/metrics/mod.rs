@@ -0,0 +1,137 @@
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use crate::helpers::write_atomic;
+use crate::logic::{Logic, Status};
+
+/// Prometheus metric, in the text exposition format
+struct Metric<'a> {
+    name: &'a str,
+    help: &'a str,
+    kind: &'a str,
+    /// Label set and value of each sample
+    samples: Vec<(String, f64)>,
+}
+
+impl Metric<'_> {
+    fn write(&self, out: &mut String) {
+        let name = format!("burnout_detector_{}", self.name);
+        let _ = writeln!(out, "# HELP {name} {}", self.help);
+        let _ = writeln!(out, "# TYPE {name} {}", self.kind);
+        for (labels, value) in &self.samples {
+            let _ = writeln!(out, "{name}{labels} {value}");
+        }
+    }
+}
+
+/// Metric with a single sample
+fn single<'a>(name: &'a str, help: &'a str, kind: &'a str, value: f64) -> Metric<'a> {
+    Metric {
+        name,
+        help,
+        kind,
+        samples: vec![(String::new(), value)],
+    }
+}
+
+/// Current state of the logic, for the node_exporter textfile collector
+pub fn render(logic: &mut Logic) -> String {
+    let (current, session) = match logic.status {
+        Status::Active(start, _) => ("active", start.elapsed()),
+        Status::Idle(start, _) => ("idle", start.elapsed()),
+        Status::Break(start, _) => ("break", start.elapsed()),
+    };
+    let status = Metric {
+        name: "status",
+        help: "Current status, 1 for the current one",
+        kind: "gauge",
+        samples: ["active", "idle", "break"]
+            .into_iter()
+            .map(|status| {
+                (
+                    format!("{{status=\"{status}\"}}"),
+                    f64::from(status == current),
+                )
+            })
+            .collect(),
+    };
+    let metrics = [
+        status,
+        single(
+            "paused",
+            "1 while the tracking is paused",
+            "gauge",
+            f64::from(logic.paused.is_some()),
+        ),
+        single(
+            "session_seconds",
+            "Duration of the current session",
+            "gauge",
+            session.as_secs() as f64,
+        ),
+        single("eyes", "Eyes shown", "gauge", logic.eyes() as f64),
+        single(
+            "notifications_counter",
+            "Notifications sent during the current active session",
+            "gauge",
+            logic.alert.counter_sent as f64,
+        ),
+        single(
+            "notifications_total",
+            "Notifications sent since the start",
+            "counter",
+            logic.alert.total_sent as f64,
+        ),
+        single(
+            "breaks_total",
+            "Idle periods and breaks since the start",
+            "counter",
+            logic.breaks as f64,
+        ),
+        single(
+            "daily_active_seconds",
+            "Active time of the day",
+            "gauge",
+            logic.active_today().as_secs() as f64,
+        ),
+    ];
+
+    let mut out = String::new();
+    for metric in &metrics {
+        metric.write(&mut out);
+    }
+    out
+}
+
+/// Write the metrics, the collector never reads a partial file
+pub fn write(path: &Path, logic: &mut Logic) -> io::Result<()> {
+    write_atomic(path, render(logic).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Args;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_render() {
+        let mut logic = Logic::new(&Args::default()).unwrap();
+        logic.alert.quiet = true;
+        logic.status = Status::Active(Instant::now() - Duration::from_secs(90), Duration::ZERO);
+        logic.set_idle();
+
+        let metrics = render(&mut logic);
+        assert!(metrics.contains(
+            "# HELP burnout_detector_status Current status, 1 for the current one\n\
+             # TYPE burnout_detector_status gauge\n\
+             burnout_detector_status{status=\"active\"} 0\n\
+             burnout_detector_status{status=\"idle\"} 1\n"
+        ));
+        assert!(metrics.contains("# TYPE burnout_detector_breaks_total counter\n"));
+        assert!(metrics.contains("burnout_detector_breaks_total 1\n"));
+        assert!(metrics.contains("burnout_detector_session_seconds 0\n"));
+        assert!(metrics.contains("burnout_detector_daily_active_seconds 90\n"));
+    }
+}
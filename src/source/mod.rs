@@ -0,0 +1,196 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::os::fd::BorrowedFd;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod stdin;
+
+use crate::logind::LogindSource;
+use crate::wayland::WaylandSource;
+use crate::x11::X11Source;
+use crate::Args;
+pub use stdin::StdinSource;
+
+/// Idle transition reported by a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// The user stopped using the computer
+    Idle,
+    /// The user is back
+    Resumed,
+    /// The session is suspended, a definitive break
+    Break,
+    /// The session is locked, a break until it is unlocked
+    Locked,
+    /// The session is unlocked
+    Unlocked,
+}
+
+impl FromStr for IdleEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "idle" => Ok(IdleEvent::Idle),
+            "resumed" | "active" => Ok(IdleEvent::Resumed),
+            "break" => Ok(IdleEvent::Break),
+            "locked" | "lock" => Ok(IdleEvent::Locked),
+            "unlocked" | "unlock" => Ok(IdleEvent::Unlocked),
+            other => Err(format!("unknown idle event '{other}'")),
+        }
+    }
+}
+
+/// Available idle sources
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// Wayland if `WAYLAND_DISPLAY` is set, X11 if `DISPLAY` is set
+    Auto,
+    /// Wayland compositor idle protocols
+    Wayland,
+    /// X11 MIT-SCREEN-SAVER extension
+    X11,
+    /// `idle` and `resumed` lines read from the standard input
+    Stdin,
+    /// No idle detection, the user is always active
+    None,
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceKind::Auto => write!(f, "auto"),
+            SourceKind::Wayland => write!(f, "wayland"),
+            SourceKind::X11 => write!(f, "x11"),
+            SourceKind::Stdin => write!(f, "stdin"),
+            SourceKind::None => write!(f, "none"),
+        }
+    }
+}
+
+impl SourceKind {
+    /// Resolve `auto` from the display environment variables
+    pub fn detect(self, wayland_display: Option<&str>, display: Option<&str>) -> SourceKind {
+        let is_set = |var: Option<&str>| var.is_some_and(|v| !v.is_empty());
+        match self {
+            SourceKind::Auto if !is_set(wayland_display) && is_set(display) => SourceKind::X11,
+            SourceKind::Auto => SourceKind::Wayland,
+            kind => kind,
+        }
+    }
+}
+
+/// Something able to tell when the user goes idle or comes back
+pub trait IdleSource {
+    /// Name of the source, for the logs
+    fn name(&self) -> &'static str;
+
+    /// Prepare the source, called once before the first dispatch
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Collect the idle events received since the last dispatch
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>>;
+
+    /// File descriptor readable when events are pending
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        None
+    }
+
+    /// Time before the next dispatch, for the sources polling their state
+    fn next_dispatch(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Why the idle detection doesn't work at the moment, none while it does
+    fn degraded(&self) -> Option<String> {
+        None
+    }
+
+    /// The source can't detect the idle time at all, another one should be used
+    fn unavailable(&self) -> bool {
+        false
+    }
+
+    /// Apply a new configuration
+    fn reload(&mut self, _args: &Args) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Source never reporting anything, useful on headless rigs
+#[derive(Debug, Default)]
+pub struct NoneSource;
+
+impl IdleSource for NoneSource {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Build the idle sources selected in the arguments
+pub fn from_args(args: &Args) -> Result<Vec<Box<dyn IdleSource>>, Box<dyn Error>> {
+    let mut sources = vec![main_source(args)?];
+    if args.logind {
+        sources.push(Box::new(LogindSource::new()?));
+    }
+
+    Ok(sources)
+}
+
+/// Build the source telling when the user is idle, or the fallback one when it can't be
+fn main_source(args: &Args) -> Result<Box<dyn IdleSource>, Box<dyn Error>> {
+    match (build(args.source, args), args.fallback_source) {
+        (Err(e), Some(fallback)) => {
+            eprintln!("{e}, falling back to the {fallback} idle source");
+            build(fallback, args)
+        }
+        (source, _) => source,
+    }
+}
+
+/// Build a source of the given kind
+pub fn build(kind: SourceKind, args: &Args) -> Result<Box<dyn IdleSource>, Box<dyn Error>> {
+    let kind = kind.detect(
+        std::env::var("WAYLAND_DISPLAY").ok().as_deref(),
+        std::env::var("DISPLAY").ok().as_deref(),
+    );
+
+    Ok(match kind {
+        SourceKind::Auto | SourceKind::Wayland => Box::new(WaylandSource::new(args)),
+        SourceKind::X11 => Box::new(X11Source::new(args)?),
+        SourceKind::Stdin => Box::new(StdinSource::new()),
+        SourceKind::None => Box::new(NoneSource),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_auto_source() {
+        let auto = SourceKind::Auto;
+
+        assert_eq!(
+            auto.detect(Some("wayland-1"), Some(":0")),
+            SourceKind::Wayland
+        );
+        assert_eq!(auto.detect(None, Some(":0")), SourceKind::X11);
+        assert_eq!(auto.detect(Some(""), Some(":0")), SourceKind::X11);
+        assert_eq!(auto.detect(None, None), SourceKind::Wayland);
+        assert_eq!(
+            SourceKind::Stdin.detect(Some("wayland-1"), None),
+            SourceKind::Stdin
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::io::BufRead;
+use std::os::fd::BorrowedFd;
+use std::thread;
+
+use crate::helpers::channel::{self, Receiver};
+use crate::source::{IdleEvent, IdleSource};
+
+/// Scripted source, reading one `idle`, `resumed` or `break` event per line on stdin
+#[derive(Debug, Default)]
+pub struct StdinSource {
+    /// Events parsed by the reader thread
+    receiver: Option<Receiver<IdleEvent>>,
+}
+
+impl StdinSource {
+    pub fn new() -> StdinSource {
+        StdinSource::default()
+    }
+}
+
+impl IdleSource for StdinSource {
+    fn name(&self) -> &'static str {
+        "stdin"
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        let (sender, receiver) = channel::channel();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match line.parse::<IdleEvent>() {
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => eprintln!("Ignoring stdin line: {e}"),
+                }
+            }
+        });
+        self.receiver = Some(receiver);
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>> {
+        Ok(self
+            .receiver
+            .as_ref()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default())
+    }
+
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.receiver.as_ref().and_then(Receiver::fd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_idle_events() {
+        assert_eq!("idle".parse::<IdleEvent>(), Ok(IdleEvent::Idle));
+        assert_eq!(" Resumed\n".parse::<IdleEvent>(), Ok(IdleEvent::Resumed));
+        assert_eq!("active".parse::<IdleEvent>(), Ok(IdleEvent::Resumed));
+        assert_eq!("break".parse::<IdleEvent>(), Ok(IdleEvent::Break));
+        assert_eq!("lock".parse::<IdleEvent>(), Ok(IdleEvent::Locked));
+        assert_eq!("unlocked".parse::<IdleEvent>(), Ok(IdleEvent::Unlocked));
+        assert!("sleeping".parse::<IdleEvent>().is_err());
+    }
+}
@@ -0,0 +1,404 @@
+use clap::{ArgGroup, Args as ClapArgs, ValueEnum};
+use hhmmss::Hhmmss;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, Time, UtcOffset};
+
+pub mod risk;
+
+use crate::history::checkpoint::{self, Checkpoint};
+use crate::history::{History, Record, SessionKind};
+use crate::logic::overtime::WorkingHours;
+use crate::stats::risk::Risk;
+use crate::Args;
+
+/// `stats` subcommand parameters
+#[derive(ClapArgs, Debug, Clone)]
+#[command(group(ArgGroup::new("period").args(["day", "week", "month"])))]
+pub struct StatsArgs {
+    /// Report since the start of the day, the default
+    #[arg(long)]
+    day: bool,
+    /// Report since monday
+    #[arg(long)]
+    week: bool,
+    /// Report since the first day of the month
+    #[arg(long)]
+    month: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+}
+
+/// Report output format
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Reported period
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Day => write!(f, "day"),
+            Period::Week => write!(f, "week"),
+            Period::Month => write!(f, "month"),
+        }
+    }
+}
+
+impl Period {
+    /// Local start of the period containing `now`
+    pub fn start(self, now: OffsetDateTime) -> OffsetDateTime {
+        let date = now.date();
+        let first_day = match self {
+            Period::Day => date,
+            Period::Week => {
+                date - time::Duration::days(date.weekday().number_days_from_monday().into())
+            }
+            Period::Month => date
+                .replace_day(1)
+                .expect("the first day of a month is valid"),
+        };
+        first_day
+            .with_time(Time::MIDNIGHT)
+            .assume_offset(now.offset())
+    }
+}
+
+/// Breaks report over a period
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub period: Period,
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    /// Total active time
+    pub active_secs: u64,
+    /// Longest uninterrupted active session
+    pub longest_session_secs: u64,
+    /// Number of idle periods and breaks
+    pub breaks: u64,
+    /// Total time of the breaks
+    pub breaks_secs: u64,
+    /// Notifications sent while the user kept working
+    pub ignored_notifications: u64,
+    /// Time spent in the critical class
+    pub critical_secs: u64,
+    /// Active time outside the working hours, when they are configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overtime_secs: Option<u64>,
+    /// Burnout risk of the last 4 weeks, up to the end of the report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<Risk>,
+}
+
+/// Seconds of the `[start, end]` interval inside `[from, to]`
+fn overlap_secs(
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+) -> u64 {
+    (end.min(to) - start.max(from)).whole_seconds().max(0) as u64
+}
+
+impl Report {
+    /// Compute the report from the records overlapping `[from, to]`
+    pub fn compute(
+        records: &[Record],
+        period: Period,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        critical_after: Duration,
+    ) -> Report {
+        let mut report = Report {
+            period,
+            from,
+            to,
+            active_secs: 0,
+            longest_session_secs: 0,
+            breaks: 0,
+            breaks_secs: 0,
+            ignored_notifications: 0,
+            critical_secs: 0,
+            overtime_secs: None,
+            risk: None,
+        };
+
+        for record in records {
+            let secs = overlap_secs(record.start, record.end, from, to);
+            if secs == 0 {
+                continue;
+            }
+            match record.kind {
+                SessionKind::Active => {
+                    report.active_secs += secs;
+                    report.longest_session_secs = report.longest_session_secs.max(secs);
+                    report.ignored_notifications += record.notifications;
+                    report.critical_secs +=
+                        overlap_secs(record.start + critical_after, record.end, from, to);
+                }
+                SessionKind::Idle | SessionKind::Break => {
+                    report.breaks += 1;
+                    report.breaks_secs += secs;
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Count the active time outside the working hours of the `offset` timezone
+    pub fn count_overtime(&mut self, records: &[Record], hours: &WorkingHours, offset: UtcOffset) {
+        let overtime = records
+            .iter()
+            .filter(|record| record.kind == SessionKind::Active)
+            .map(|record| (record.start.max(self.from), record.end.min(self.to)))
+            .filter(|(start, end)| start < end)
+            .map(|(start, end)| hours.overtime_secs(start, end, offset))
+            .sum();
+        self.overtime_secs = Some(overtime);
+    }
+
+    fn average_break_secs(&self) -> u64 {
+        self.breaks_secs.checked_div(self.breaks).unwrap_or(0)
+    }
+
+    pub fn to_table(&self) -> String {
+        let hms = |secs: u64| Duration::from_secs(secs).hhmmss();
+        let mut lines = vec![
+            format!("{:<24}{} since {}", "Period", self.period, self.from.date()),
+            format!("{:<24}{}", "Active time", hms(self.active_secs)),
+            format!(
+                "{:<24}{}",
+                "Longest session",
+                hms(self.longest_session_secs)
+            ),
+            format!(
+                "{:<24}{} (total {}, average {})",
+                "Breaks",
+                self.breaks,
+                hms(self.breaks_secs),
+                hms(self.average_break_secs())
+            ),
+            format!(
+                "{:<24}{}",
+                "Ignored notifications", self.ignored_notifications
+            ),
+            format!("{:<24}{}", "Critical time", hms(self.critical_secs)),
+        ];
+        if let Some(overtime_secs) = self.overtime_secs {
+            lines.push(format!("{:<24}{}", "Overtime", hms(overtime_secs)));
+        }
+        if let Some(risk) = &self.risk {
+            lines.push(risk.to_table());
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_csv(&self) -> String {
+        let rfc3339 = |date: OffsetDateTime| date.format(&Rfc3339).unwrap_or_default();
+        format!(
+            "period,from,to,active_secs,longest_session_secs,breaks,breaks_secs,ignored_notifications,critical_secs,overtime_secs,risk_score\n{},{},{},{},{},{},{},{},{},{},{}",
+            self.period,
+            rfc3339(self.from),
+            rfc3339(self.to),
+            self.active_secs,
+            self.longest_session_secs,
+            self.breaks,
+            self.breaks_secs,
+            self.ignored_notifications,
+            self.critical_secs,
+            self.overtime_secs
+                .map(|secs| secs.to_string())
+                .unwrap_or_default(),
+            self.risk
+                .as_ref()
+                .map(|risk| risk.score.to_string())
+                .unwrap_or_default()
+        )
+    }
+}
+
+/// The checkpointed session, if it's the latest one and not in the history yet
+fn current_session(records: &[Record], checkpoint: Option<Checkpoint>) -> Option<Record> {
+    let checkpoint = checkpoint?;
+    let last_end = records.last().map(|record| record.end);
+    if last_end.is_some_and(|end| checkpoint.start < end - time::Duration::SECOND) {
+        return None;
+    }
+
+    Some(Record {
+        start: checkpoint.start,
+        end: checkpoint.saved_at,
+        duration_secs: (checkpoint.saved_at - checkpoint.start)
+            .whole_seconds()
+            .max(0) as u64,
+        kind: checkpoint.kind,
+        notifications: checkpoint.counter_sent,
+    })
+}
+
+/// Print the report of the recorded history
+pub fn run(args: &Args, stats_args: &StatsArgs) -> Result<(), Box<dyn Error>> {
+    let history = History::open_default().ok_or("Can't find the state directory")?;
+    let mut records = history.read_all()?;
+    let checkpoint = checkpoint::default_path().and_then(|path| Checkpoint::load(&path));
+    records.extend(current_session(&records, checkpoint));
+
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let now = OffsetDateTime::now_utc().to_offset(offset);
+    let period = if stats_args.week {
+        Period::Week
+    } else if stats_args.month {
+        Period::Month
+    } else {
+        Period::Day
+    };
+    let critical_after = Duration::from_secs(args.idle_timeout * args.max_active_sessions);
+    let mut report = Report::compute(&records, period, period.start(now), now, critical_after);
+    if let Some(hours) = &args.working_hours {
+        report.count_overtime(&records, hours, offset);
+    }
+    report.risk = Some(Risk::compute(&records, now));
+
+    match stats_args.format {
+        Format::Table => println!("{}", report.to_table()),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        Format::Csv => println!("{}", report.to_csv()),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{TimeRange, WorkingHoursConfig};
+    use time::macros::{datetime, time};
+
+    fn record(
+        start: OffsetDateTime,
+        minutes: i64,
+        kind: SessionKind,
+        notifications: u64,
+    ) -> Record {
+        Record {
+            start,
+            end: start + time::Duration::minutes(minutes),
+            duration_secs: minutes as u64 * 60,
+            kind,
+            notifications,
+        }
+    }
+
+    #[test]
+    fn test_period_start() {
+        // a wednesday
+        let now = datetime!(2023-11-15 14:30 +1);
+
+        assert_eq!(Period::Day.start(now), datetime!(2023-11-15 00:00 +1));
+        assert_eq!(Period::Week.start(now), datetime!(2023-11-13 00:00 +1));
+        assert_eq!(Period::Month.start(now), datetime!(2023-11-01 00:00 +1));
+    }
+
+    #[test]
+    fn test_report_of_a_day() {
+        let records = vec![
+            // yesterday evening, only the last 30min count
+            record(datetime!(2023-11-14 23:00 UTC), 90, SessionKind::Active, 1),
+            record(datetime!(2023-11-15 00:30 UTC), 480, SessionKind::Break, 0),
+            record(datetime!(2023-11-15 08:30 UTC), 50, SessionKind::Active, 0),
+            record(datetime!(2023-11-15 09:20 UTC), 10, SessionKind::Idle, 0),
+            record(datetime!(2023-11-15 09:30 UTC), 120, SessionKind::Active, 4),
+            record(datetime!(2023-11-15 11:30 UTC), 20, SessionKind::Idle, 0),
+        ];
+        let from = datetime!(2023-11-15 00:00 UTC);
+        let to = datetime!(2023-11-15 12:00 UTC);
+
+        let report = Report::compute(
+            &records,
+            Period::Day,
+            from,
+            to,
+            Duration::from_secs(30 * 60),
+        );
+
+        assert_eq!(report.active_secs, (30 + 50 + 120) * 60);
+        assert_eq!(report.longest_session_secs, 120 * 60);
+        assert_eq!(report.breaks, 3);
+        assert_eq!(report.breaks_secs, (480 + 10 + 20) * 60);
+        assert_eq!(report.average_break_secs(), 170 * 60);
+        assert_eq!(report.ignored_notifications, 5);
+        // critical after 30min: 30min after midnight, 20min of 08:30, 90min of 09:30
+        assert_eq!(report.critical_secs, (30 + 20 + 90) * 60);
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("period,from,to,active_secs"));
+        assert_eq!(
+            lines.next().unwrap(),
+            "day,2023-11-15T00:00:00Z,2023-11-15T12:00:00Z,12000,7200,3,30600,5,8400,,"
+        );
+
+        // from 9:00, the 30min after midnight and the 30min from 08:30 are overtime
+        let config = WorkingHoursConfig {
+            weekdays: Some(TimeRange {
+                start: time!(09:00),
+                end: time!(18:00),
+            }),
+            ..WorkingHoursConfig::default()
+        };
+        let mut report = report;
+        report.count_overtime(&records, &WorkingHours::new(&config), UtcOffset::UTC);
+        assert_eq!(report.overtime_secs, Some(60 * 60));
+        assert!(report
+            .to_table()
+            .ends_with("Overtime                01:00:00"));
+        assert!(report.to_csv().ends_with(",3600,"));
+    }
+
+    #[test]
+    fn test_current_session_from_checkpoint() {
+        let records = vec![record(
+            datetime!(2023-11-15 08:00 UTC),
+            30,
+            SessionKind::Idle,
+            0,
+        )];
+        let checkpoint = Checkpoint {
+            kind: SessionKind::Active,
+            start: datetime!(2023-11-15 08:30 UTC),
+            saved_at: datetime!(2023-11-15 09:00 UTC),
+            counter_sent: 1,
+            next_send_time: Duration::ZERO,
+            notification_delay: Duration::ZERO,
+        };
+
+        let current = current_session(&records, Some(checkpoint.clone())).unwrap();
+        assert_eq!(current.duration_secs, 30 * 60);
+        assert_eq!(current.notifications, 1);
+
+        // already recorded by a restart
+        let records = vec![record(checkpoint.start, 30, SessionKind::Active, 1)];
+        assert_eq!(current_session(&records, Some(checkpoint)), None);
+    }
+}
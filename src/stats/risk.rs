@@ -0,0 +1,439 @@
+use hhmmss::Hhmmss;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, Time, Weekday};
+
+use crate::helpers::{state_dir, write_atomic};
+use crate::history::{Record, SessionKind};
+
+/// Days of history looked at, the last one is the current day
+const WINDOW_DAYS: i64 = 28;
+/// Days of the recent period compared to the previous ones
+const RECENT_DAYS: i64 = 7;
+/// A day with less active time than this is a rest day
+const REST_DAY_SECS: u64 = 3600;
+/// Late night, from this hour to `NIGHT_END`
+const NIGHT_START: u8 = 22;
+const NIGHT_END: u8 = 6;
+
+/// Part of the score, `value` is mapped linearly to 0 at `from` and `weight` at `to`
+struct Factor {
+    weight: f64,
+    from: f64,
+    to: f64,
+}
+
+impl Factor {
+    fn points(&self, value: f64) -> f64 {
+        self.weight * ((value - self.from) / (self.to - self.from)).clamp(0.0, 1.0)
+    }
+}
+
+/// Active hours of the last 7 days, from 35h to 55h
+const WEEKLY_HOURS: Factor = Factor {
+    weight: 30.0,
+    from: 35.0,
+    to: 55.0,
+};
+/// Increase of the last 7 days over the weekly average of the up to 3 previous weeks, up to +50%
+const TREND: Factor = Factor {
+    weight: 10.0,
+    from: 0.0,
+    to: 0.5,
+};
+/// Notifications worked through per day over the last 7 days, up to 10
+const SKIPPED_BREAKS: Factor = Factor {
+    weight: 20.0,
+    from: 0.0,
+    to: 10.0,
+};
+/// Active hours between 22:00 and 06:00 over the last 7 days, up to 5h
+const LATE_NIGHT: Factor = Factor {
+    weight: 15.0,
+    from: 0.0,
+    to: 5.0,
+};
+/// Active hours on the last weekend days, up to 8h
+const WEEKEND: Factor = Factor {
+    weight: 10.0,
+    from: 0.0,
+    to: 8.0,
+};
+/// Longest streak of days without a rest day over the last 28 days, from 6 to 14 days
+const STREAK: Factor = Factor {
+    weight: 15.0,
+    from: 6.0,
+    to: 14.0,
+};
+
+/// How worrying the score is
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "low"),
+            RiskLevel::Moderate => write!(f, "moderate"),
+            RiskLevel::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Long term burnout risk, from 0 to 100, computed from the last 4 weeks of history.
+///
+/// The score is the sum of 6 factors, each one growing linearly between two bounds:
+/// - weekly active hours, 30 points from 35h to 55h over the last 7 days
+/// - trend, 10 points from +0% to +50% of the last 7 days over the up to 3 weeks before,
+///   left out when the history doesn't cover a week before them
+/// - skipped breaks, 20 points from 0 to 10 notifications worked through per day
+/// - late night, 15 points from 0 to 5h active between 22:00 and 06:00 over the last 7 days
+/// - weekend, 10 points from 0 to 8h active on saturday and sunday over the last 7 days
+/// - streak, 15 points from 6 to 14 days in a row without a rest day (less than 1h active)
+///
+/// Below 30 the risk is low, below 60 moderate, and high above.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Risk {
+    pub score: u8,
+    pub level: RiskLevel,
+    /// Active time of the last 7 days
+    pub weekly_active_secs: u64,
+    /// Weekly active time average of the previous weeks the history covers, 0 under a week
+    pub previous_weekly_active_secs: u64,
+    /// Notifications worked through in the last 7 days
+    pub skipped_breaks: u64,
+    /// Active time late at night in the last 7 days
+    pub late_night_secs: u64,
+    /// Active time on the weekend in the last 7 days
+    pub weekend_secs: u64,
+    /// Longest streak of days without a rest day
+    pub streak_days: u64,
+}
+
+/// Local time of `date` at `hour`, the next midnight for 24
+fn at(date: Date, hour: u8, now: OffsetDateTime) -> OffsetDateTime {
+    match Time::from_hms(hour, 0, 0) {
+        Ok(time) => date.with_time(time).assume_offset(now.offset()),
+        Err(_) => date.with_time(Time::MIDNIGHT).assume_offset(now.offset()) + time::Duration::DAY,
+    }
+}
+
+/// Seconds of `[start, end]` inside `[from, to]`
+fn overlap(
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+) -> u64 {
+    (end.min(to) - start.max(from)).whole_seconds().max(0) as u64
+}
+
+/// Active time of each local day
+#[derive(Debug, Default)]
+struct Day {
+    active_secs: u64,
+    late_night_secs: u64,
+}
+
+impl Risk {
+    /// Risk at `now`, a local time, from the records
+    pub fn compute(records: &[Record], now: OffsetDateTime) -> Risk {
+        let today = now.date();
+        let first_day = today - time::Duration::days(WINDOW_DAYS - 1);
+        let recent_start = at(today - time::Duration::days(RECENT_DAYS - 1), 0, now);
+
+        let mut days: BTreeMap<Date, Day> = BTreeMap::new();
+        let mut skipped_breaks = 0;
+        for record in records.iter().filter(|r| r.kind == SessionKind::Active) {
+            if record.end > recent_start && record.start <= now {
+                skipped_breaks += record.notifications;
+            }
+            let start = record.start.to_offset(now.offset());
+            let end = record.end.to_offset(now.offset()).min(now);
+            let mut date = start.date().max(first_day);
+            while date <= end.date() && date <= today {
+                let day = days.entry(date).or_default();
+                day.active_secs += overlap(start, end, at(date, 0, now), at(date, 24, now));
+                day.late_night_secs +=
+                    overlap(start, end, at(date, 0, now), at(date, NIGHT_END, now))
+                        + overlap(start, end, at(date, NIGHT_START, now), at(date, 24, now));
+                let Some(next) = date.next_day() else {
+                    break;
+                };
+                date = next;
+            }
+        }
+
+        let recent = |date: &&Date| **date > today - time::Duration::days(RECENT_DAYS);
+        let weekly_active_secs = days
+            .iter()
+            .filter(|(date, _)| recent(date))
+            .map(|(_, day)| day.active_secs)
+            .sum();
+        let previous_secs: u64 = days
+            .iter()
+            .filter(|(date, _)| !recent(date))
+            .map(|(_, day)| day.active_secs)
+            .sum();
+        // the days of the previous weeks the history covers, none before its first record
+        let recent_first_day = today - time::Duration::days(RECENT_DAYS - 1);
+        let history_start = records
+            .iter()
+            .map(|record| record.start.to_offset(now.offset()).date())
+            .min()
+            .map_or(recent_first_day, |date| date.max(first_day));
+        let previous_days = (recent_first_day - history_start).whole_days();
+        // too short for a weekly average, the trend is left out
+        let previous_weekly_active_secs = if previous_days < RECENT_DAYS {
+            0
+        } else {
+            previous_secs * RECENT_DAYS as u64 / previous_days as u64
+        };
+        let late_night_secs = days
+            .iter()
+            .filter(|(date, _)| recent(date))
+            .map(|(_, day)| day.late_night_secs)
+            .sum();
+        let weekend_secs = days
+            .iter()
+            .filter(|(date, _)| recent(date))
+            .filter(|(date, _)| matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday))
+            .map(|(_, day)| day.active_secs)
+            .sum();
+
+        let mut streak_days = 0;
+        let mut streak = 0;
+        let mut date = first_day;
+        while date <= today {
+            let worked = days
+                .get(&date)
+                .is_some_and(|day| day.active_secs >= REST_DAY_SECS);
+            streak = if worked { streak + 1 } else { 0 };
+            streak_days = streak_days.max(streak);
+            let Some(next) = date.next_day() else {
+                break;
+            };
+            date = next;
+        }
+
+        let mut risk = Risk {
+            score: 0,
+            level: RiskLevel::Low,
+            weekly_active_secs,
+            previous_weekly_active_secs,
+            skipped_breaks,
+            late_night_secs,
+            weekend_secs,
+            streak_days,
+        };
+        risk.score = risk.points().round() as u8;
+        risk.level = match risk.score {
+            0..=29 => RiskLevel::Low,
+            30..=59 => RiskLevel::Moderate,
+            _ => RiskLevel::High,
+        };
+
+        risk
+    }
+
+    /// Sum of the factors, see the `Risk` documentation
+    fn points(&self) -> f64 {
+        let hours = |secs: u64| secs as f64 / 3600.0;
+        let trend = if self.previous_weekly_active_secs == 0 {
+            0.0
+        } else {
+            self.weekly_active_secs as f64 / self.previous_weekly_active_secs as f64 - 1.0
+        };
+
+        WEEKLY_HOURS.points(hours(self.weekly_active_secs))
+            + TREND.points(trend)
+            + SKIPPED_BREAKS.points(self.skipped_breaks as f64 / RECENT_DAYS as f64)
+            + LATE_NIGHT.points(hours(self.late_night_secs))
+            + WEEKEND.points(hours(self.weekend_secs))
+            + STREAK.points(self.streak_days as f64)
+    }
+
+    /// One line summary, for the tooltip
+    pub fn describe(&self) -> String {
+        format!("Burnout risk {}/100 ({})", self.score, self.level)
+    }
+
+    /// Detailed lines, for the stats and the weekly summary
+    pub fn to_table(&self) -> String {
+        let hms = |secs: u64| Duration::from_secs(secs).hhmmss();
+        [
+            format!("{:<24}{}/100 ({})", "Burnout risk", self.score, self.level),
+            format!(
+                "{:<24}{} (previous weeks {})",
+                "Active last 7 days",
+                hms(self.weekly_active_secs),
+                hms(self.previous_weekly_active_secs)
+            ),
+            format!("{:<24}{}", "Skipped breaks", self.skipped_breaks),
+            format!("{:<24}{}", "Late night", hms(self.late_night_secs)),
+            format!("{:<24}{}", "Weekend", hms(self.weekend_secs)),
+            format!("{:<24}{} days", "Days without rest", self.streak_days),
+        ]
+        .join("\n")
+    }
+}
+
+/// Date of the last weekly summary, in `$XDG_STATE_HOME/burnout-detector`
+pub fn summary_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("weekly_summary"))
+}
+
+/// Date of the last weekly summary sent, none when never sent
+pub fn last_summary(path: &Path) -> Option<Date> {
+    let content = fs::read_to_string(path).ok()?;
+    Date::parse(content.trim(), format_description!("[year]-[month]-[day]")).ok()
+}
+
+/// Remember the date of the weekly summary
+pub fn save_summary(path: &Path, date: Date) -> io::Result<()> {
+    write_atomic(path, date.to_string().as_bytes())
+}
+
+/// Tell if the summary of the week of `today` wasn't sent yet, the week starts on monday
+pub fn summary_due(last: Option<Date>, today: Date) -> bool {
+    let monday = today - time::Duration::days(today.weekday().number_days_from_monday().into());
+    match last {
+        Some(last) => last < monday,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+
+    fn active(start: OffsetDateTime, hours: i64, notifications: u64) -> Record {
+        Record {
+            start,
+            end: start + time::Duration::hours(hours),
+            duration_secs: hours as u64 * 3600,
+            kind: SessionKind::Active,
+            notifications,
+        }
+    }
+
+    #[test]
+    fn test_factors() {
+        assert_eq!(WEEKLY_HOURS.points(20.0), 0.0);
+        assert_eq!(WEEKLY_HOURS.points(45.0), 15.0);
+        assert_eq!(WEEKLY_HOURS.points(80.0), 30.0);
+        assert_eq!(STREAK.points(10.0), 7.5);
+    }
+
+    #[test]
+    fn test_no_history_no_risk() {
+        let risk = Risk::compute(&[], datetime!(2026-03-15 12:00 UTC));
+        assert_eq!(risk.score, 0);
+        assert_eq!(risk.level, RiskLevel::Low);
+        assert_eq!(risk.streak_days, 0);
+    }
+
+    #[test]
+    fn test_risk_of_a_heavy_month() {
+        // sunday
+        let now = datetime!(2026-03-15 23:30 UTC);
+        let mut records = Vec::new();
+        // 3 weeks of 8h days from monday to friday
+        for day in 0..21 {
+            let date = date!(2026 - 02 - 16) + time::Duration::days(day);
+            if date.weekday().number_days_from_monday() < 5 {
+                records.push(active(
+                    date.midnight().assume_utc() + time::Duration::hours(9),
+                    8,
+                    0,
+                ));
+            }
+        }
+        // then 10h every day, until 23:00, with 3 notifications worked through
+        for day in 0..7 {
+            let date = date!(2026 - 03 - 09) + time::Duration::days(day);
+            records.push(active(
+                date.midnight().assume_utc() + time::Duration::hours(13),
+                10,
+                3,
+            ));
+        }
+
+        let risk = Risk::compute(&records, now);
+        assert_eq!(risk.weekly_active_secs, 70 * 3600);
+        assert_eq!(risk.previous_weekly_active_secs, 40 * 3600);
+        assert_eq!(risk.skipped_breaks, 21);
+        assert_eq!(risk.late_night_secs, 7 * 3600);
+        assert_eq!(risk.weekend_secs, 20 * 3600);
+        // the weekends were rest days until this one
+        assert_eq!(risk.streak_days, 7);
+        // 30 + 10 + 6 + 15 + 10 + 1.875
+        assert_eq!(risk.score, 73);
+        assert_eq!(risk.level, RiskLevel::High);
+        assert_eq!(risk.describe(), "Burnout risk 73/100 (high)");
+    }
+
+    #[test]
+    fn test_short_history() {
+        // sunday
+        let now = datetime!(2026-03-15 23:30 UTC);
+        // 10 days of 8h, from friday
+        let records: Vec<Record> = (0..10)
+            .map(|day| {
+                let date = date!(2026 - 03 - 06) + time::Duration::days(day);
+                active(
+                    date.midnight().assume_utc() + time::Duration::hours(9),
+                    8,
+                    0,
+                )
+            })
+            .collect();
+
+        // 3 days before the last 7, too short for a trend
+        let risk = Risk::compute(&records, now);
+        assert_eq!(risk.weekly_active_secs, 7 * 8 * 3600);
+        assert_eq!(risk.previous_weekly_active_secs, 0);
+
+        // 10 days before the last 7, 80h over 10 days
+        let records: Vec<Record> = (0..7)
+            .map(|day| {
+                let date = date!(2026 - 02 - 27) + time::Duration::days(day);
+                active(
+                    date.midnight().assume_utc() + time::Duration::hours(9),
+                    8,
+                    0,
+                )
+            })
+            .chain(records)
+            .collect();
+        let risk = Risk::compute(&records, now);
+        assert_eq!(risk.previous_weekly_active_secs, 80 * 3600 * 7 / 10);
+    }
+
+    #[test]
+    fn test_summary_once_a_week() {
+        // wednesday
+        let today = date!(2026 - 03 - 11);
+        assert!(summary_due(None, today));
+        assert!(summary_due(Some(date!(2026 - 03 - 08)), today));
+        assert!(!summary_due(Some(date!(2026 - 03 - 09)), today));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("weekly_summary");
+        assert_eq!(last_summary(&path), None);
+        save_summary(&path, today).unwrap();
+        assert_eq!(last_summary(&path), Some(today));
+    }
+}
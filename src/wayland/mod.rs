@@ -1,31 +1,183 @@
-use chan::chan_select;
+use clap::ValueEnum;
+use serde::Deserialize;
 use std::error::Error;
-use std::thread;
-use std::time::Duration;
-use wayland_client::EventQueue;
+use std::fmt;
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::time::{Duration, Instant};
+use wayland_client::backend::WaylandError as BackendError;
 use wayland_client::{
-    protocol::wl_registry, protocol::wl_seat, protocol::wl_seat::WlSeat, Connection, Dispatch,
-    QueueHandle,
+    protocol::wl_registry, protocol::wl_seat, protocol::wl_seat::WlSeat, ConnectError, Connection,
+    Dispatch, DispatchError, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::idle_notify::v1::client::ext_idle_notifier_v1::ExtIdleNotifierV1;
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1, ext_idle_notifier_v1,
 };
 use wayland_protocols_plasma::idle::client::org_kde_kwin_idle::OrgKdeKwinIdle;
 use wayland_protocols_plasma::idle::client::{org_kde_kwin_idle, org_kde_kwin_idle_timeout};
 
-use crate::logic::Logic;
+use crate::helpers::idle_detection_timeout;
+use crate::source::{IdleEvent, IdleSource};
 use crate::Args;
 
+/// Highest `wl_seat` version we need, we don't use any seat event
+const WL_SEAT_VERSION: u32 = 1;
+/// Highest `ext_idle_notifier_v1` version we know, v2 adds `get_input_idle_notification`
+const EXT_IDLE_NOTIFIER_VERSION: u32 = 2;
+/// Highest `org_kde_kwin_idle` version we know
+const KDE_IDLE_VERSION: u32 = 1;
+/// First delay before reconnecting to the compositor
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Longest delay between two reconnection attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+/// Time given to the compositor to advertise an idle protocol once connected
+const PROTOCOL_WINDOW: Duration = Duration::from_secs(5);
+
+/// Idle protocol used to detect the user activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleProtocol {
+    /// Standard `ext_idle_notifier_v1` protocol
+    ExtIdleNotify,
+    /// Legacy KDE `org_kde_kwin_idle` protocol
+    KdeIdle,
+}
+
+impl fmt::Display for IdleProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdleProtocol::ExtIdleNotify => write!(f, "ext_idle_notifier_v1"),
+            IdleProtocol::KdeIdle => write!(f, "org_kde_kwin_idle"),
+        }
+    }
+}
+
+/// How the idle state of several seats makes the user status
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeatPolicy {
+    /// Active while any seat is active, idle once all of them are
+    AnyActive,
+    /// Active while all the seats are active, idle as soon as one is
+    AllActive,
+}
+
+impl SeatPolicy {
+    /// Whether the user is idle, from the idle state of each seat
+    fn is_idle(self, mut seats: impl Iterator<Item = bool>) -> bool {
+        match self {
+            SeatPolicy::AnyActive => seats.all(|idle| idle),
+            SeatPolicy::AllActive => seats.any(|idle| idle),
+        }
+    }
+}
+
+/// Idle notification object created on the seat
+#[derive(Debug)]
+enum IdleNotification {
+    Ext(ext_idle_notification_v1::ExtIdleNotificationV1),
+    Kde(org_kde_kwin_idle_timeout::OrgKdeKwinIdleTimeout),
+}
+
+impl IdleNotification {
+    fn destroy(self) {
+        match self {
+            IdleNotification::Ext(notification) => notification.destroy(),
+            IdleNotification::Kde(timeout) => timeout.release(),
+        }
+    }
+}
+
+/// Seat tracked for idle detection
+#[derive(Debug)]
+struct Seat {
+    /// Registry name of the `wl_seat` global
+    name: u32,
+    seat: WlSeat,
+    /// Idle notification object, once the protocol is known
+    notification: Option<IdleNotification>,
+    /// Idle according to its notification
+    idle: bool,
+}
+
 /// State of the app
 #[derive(Debug)]
 pub struct Wayland {
-    /// Wayland seat
-    seat: Option<WlSeat>,
+    /// Wayland seats
+    seats: Vec<Seat>,
+    /// Standard idle notifier
+    ext_idle: Option<ExtIdleNotifierV1>,
     /// KDE idle
     kde_idle: Option<OrgKdeKwinIdle>,
-    /// Logic struct
-    logic: Logic,
+    /// Idle protocol in use, once chosen
+    protocol: Option<IdleProtocol>,
+    /// Combines the seats idle state
+    policy: SeatPolicy,
+    /// Idle state last reported
+    idle: bool,
+    /// The initial globals have been received
+    globals_done: bool,
+    /// Interfaces advertised by the compositor
+    globals: Vec<String>,
+    /// Idle events received since the last dispatch
+    events: Vec<IdleEvent>,
     /// Idle detection timeout
     timeout: u32,
 }
 
+/// Wayland idle source errors
+#[derive(Debug)]
+pub enum WaylandError {
+    /// No compositor to connect to
+    Connect(ConnectError),
+    /// The connection is broken
+    Backend(BackendError),
+    /// The events can't be dispatched
+    Dispatch(DispatchError),
+}
+
+impl fmt::Display for WaylandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaylandError::Connect(e) => write!(f, "Can't connect to the Wayland compositor: {e}"),
+            WaylandError::Backend(e) => write!(f, "Lost the Wayland compositor connection: {e}"),
+            WaylandError::Dispatch(e) => write!(f, "Can't dispatch the Wayland events: {e}"),
+        }
+    }
+}
+
+impl Error for WaylandError {}
+
+/// Delay before reconnecting, doubled after each failed attempt up to a cap
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Backoff {
+    delay: Duration,
+    /// Next attempt, none while connected
+    next: Option<Instant>,
+}
+
+impl Backoff {
+    fn new() -> Backoff {
+        Backoff {
+            delay: RECONNECT_DELAY,
+            next: None,
+        }
+    }
+
+    /// An attempt failed, wait longer before the next one
+    fn failed(&mut self, now: Instant) {
+        self.next = Some(now + self.delay);
+        self.delay = (self.delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+
+    /// Time until the next attempt
+    fn remaining(&self, now: Instant) -> Duration {
+        self.next
+            .map(|next| next.saturating_duration_since(now))
+            .unwrap_or_default()
+    }
+}
+
 /// Wayland events
 #[derive(Debug)]
 pub struct Events {
@@ -34,8 +186,8 @@ pub struct Events {
 }
 
 impl Events {
-    pub fn new() -> Result<Events, Box<dyn Error>> {
-        let conn = Connection::connect_to_env().expect("Can't connect to wayland");
+    pub fn new() -> Result<Events, WaylandError> {
+        let conn = Connection::connect_to_env().map_err(WaylandError::Connect)?;
         let display = conn.display();
         let event_queue = conn.new_event_queue();
         let queue_handle = event_queue.handle();
@@ -43,54 +195,362 @@ impl Events {
 
         Ok(Events { conn, event_queue })
     }
+
+    /// Read and dispatch the events sent by the compositor
+    fn dispatch(&mut self, state: &mut Wayland) -> Result<(), WaylandError> {
+        // none is pending in the queue when it was dispatched already
+        if let Some(guard) = self.conn.prepare_read() {
+            match guard.read() {
+                Ok(_) => {}
+                Err(BackendError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(WaylandError::Backend(e)),
+            }
+        }
+        self.event_queue
+            .dispatch_pending(state)
+            .map_err(WaylandError::Dispatch)?;
+        // send the requests of the handlers before the main loop sleeps
+        self.flush()
+    }
+
+    /// Send the pending requests
+    fn flush(&self) -> Result<(), WaylandError> {
+        match self.conn.flush() {
+            Err(BackendError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            result => result.map_err(WaylandError::Backend),
+        }
+    }
+}
+
+/// Wayland idle source
+#[derive(Debug)]
+pub struct WaylandSource {
+    /// Compositor connection, none until reconnected
+    events: Option<Events>,
+    state: Wayland,
+    backoff: Backoff,
+    /// Why the connection was lost
+    error: Option<String>,
+    /// Connection time, or start time until the first connection, an idle protocol is expected soon after
+    connected: Instant,
+    /// A connection was established once, a lost one is only a transient problem
+    ever_connected: bool,
+    /// The missing idle protocol was logged
+    reported: bool,
 }
 
 impl Wayland {
-    pub fn new(args: &Args) -> Result<Wayland, ()> {
-        let logic = Logic::new(args).expect("Can't initialize logic core");
-        let timeout = if args.idle_timeout < 60 {
-            // if idle_timeout less than 60s,
-            // make wayland timeout 1s less
-            (args.idle_timeout as u32 - 1) * 1000
+    pub fn new(timeout: u32, policy: SeatPolicy) -> Wayland {
+        Wayland {
+            seats: Vec::new(),
+            ext_idle: None,
+            kde_idle: None,
+            protocol: None,
+            policy,
+            idle: false,
+            globals_done: false,
+            globals: Vec::new(),
+            events: Vec::new(),
+            timeout,
+        }
+    }
+
+    /// Create the idle notification objects of the seats lacking one, preferring
+    /// `ext_idle_notifier_v1` over `org_kde_kwin_idle` when both are advertised
+    fn request_idle_notification(&mut self, qh: &QueueHandle<Wayland>) {
+        if !self.globals_done {
+            return;
+        }
+        let protocol = match (self.protocol, &self.ext_idle, &self.kde_idle) {
+            (Some(protocol), _, _) => protocol,
+            (None, Some(_), _) => IdleProtocol::ExtIdleNotify,
+            (None, None, Some(_)) => IdleProtocol::KdeIdle,
+            (None, None, None) => return,
+        };
+
+        for seat in self
+            .seats
+            .iter_mut()
+            .filter(|seat| seat.notification.is_none())
+        {
+            seat.notification = match protocol {
+                IdleProtocol::ExtIdleNotify => self.ext_idle.as_ref().map(|ext_idle| {
+                    IdleNotification::Ext(if ext_idle.version() >= 2 {
+                        // ignore idle inhibitors, a video playing is not someone working
+                        ext_idle.get_input_idle_notification(
+                            self.timeout,
+                            &seat.seat,
+                            qh,
+                            seat.name,
+                        )
+                    } else {
+                        ext_idle.get_idle_notification(self.timeout, &seat.seat, qh, seat.name)
+                    })
+                }),
+                IdleProtocol::KdeIdle => self.kde_idle.as_ref().map(|kde_idle| {
+                    IdleNotification::Kde(kde_idle.get_idle_timeout(
+                        &seat.seat,
+                        self.timeout,
+                        qh,
+                        seat.name,
+                    ))
+                }),
+            };
+            if seat.notification.is_some() {
+                self.protocol = Some(protocol);
+                eprintln!("Using {protocol} for idle detection on seat {}", seat.name);
+            }
+        }
+        // new notifications start active, report the user back if it was idle
+        self.update_idle();
+    }
+
+    /// Destroy the idle notification objects, to request them again
+    fn release_notifications(&mut self) {
+        for seat in self.seats.iter_mut() {
+            if let Some(notification) = seat.notification.take() {
+                notification.destroy();
+            }
+            seat.idle = false;
+        }
+        self.protocol = None;
+    }
+
+    /// Some seat has an idle notification
+    fn is_tracking(&self) -> bool {
+        self.seats.iter().any(|seat| seat.notification.is_some())
+    }
+
+    /// A seat went idle or came back
+    fn set_seat_idle(&mut self, name: u32, idle: bool) {
+        if let Some(seat) = self.seats.iter_mut().find(|seat| seat.name == name) {
+            seat.idle = idle;
+        }
+        self.update_idle();
+    }
+
+    /// Report the user status made by the seat policy when it changes
+    fn update_idle(&mut self) {
+        if !self.is_tracking() {
+            return;
+        }
+        let seats = self
+            .seats
+            .iter()
+            .filter(|seat| seat.notification.is_some())
+            .map(|seat| seat.idle);
+        let idle = self.policy.is_idle(seats);
+        if idle != self.idle {
+            self.idle = idle;
+            self.events.push(if idle {
+                IdleEvent::Idle
+            } else {
+                IdleEvent::Resumed
+            });
+        }
+    }
+
+    /// Why no idle notification was created
+    fn missing_protocol(&self) -> String {
+        if self.seats.is_empty() && (self.ext_idle.is_some() || self.kde_idle.is_some()) {
+            "the compositor advertises no seat".into()
+        } else if self.ext_idle.is_none() && self.kde_idle.is_none() {
+            "the compositor advertises neither ext_idle_notifier_v1 nor org_kde_kwin_idle".into()
         } else {
-            // or use 60s as timeout detection
-            60 * 1000
+            "no idle notification could be created".into()
+        }
+    }
+}
+
+impl WaylandSource {
+    /// Connect to the compositor, or retry later like after a lost connection
+    pub fn new(args: &Args) -> WaylandSource {
+        let timeout = idle_detection_timeout(args.idle_timeout).as_millis() as u32;
+        let mut source = WaylandSource {
+            events: None,
+            state: Wayland::new(timeout, args.seat_policy),
+            backoff: Backoff::new(),
+            error: None,
+            connected: Instant::now(),
+            ever_connected: false,
+            reported: false,
         };
+        match Events::new() {
+            Ok(events) => source.set_connection(events),
+            Err(e) => source.disconnect(e),
+        }
 
-        Ok(Wayland {
-            seat: None,
-            kde_idle: None,
-            logic,
-            timeout,
-        })
+        source
     }
 
-    pub fn run(mut self) -> ! {
-        let mut events = Events::new().expect("Can't initialize events");
+    /// A connection to the compositor is established
+    fn set_connection(&mut self, events: Events) {
+        self.events = Some(events);
+        self.ever_connected = true;
+    }
 
-        let tick_dispatch = chan::tick(Duration::from_millis(500));
-        let tick_compute = chan::tick(Duration::from_millis(1000));
-        loop {
-            chan_select! {
-                default => {
-                    thread::sleep(Duration::from_millis(100));
-                },
-                tick_dispatch.recv() => {
-                    events.event_queue.dispatch_pending(&mut self).expect("Can't dispatch wayland events");
-                    // flush and read events
-                    events.conn.flush().is_ok().then(|| {
-                        events.conn.prepare_read().expect("Can't prepare wayland read").read()
-                    });
-                },
-                tick_compute.recv() => {
-                    self.logic.run().expect("Can't run the main idle analyze job");
-                },
+    /// Wait for all the advertised globals before choosing the idle protocol
+    fn setup(&mut self) -> Result<(), WaylandError> {
+        let Some(events) = self.events.as_mut() else {
+            return Ok(());
+        };
+        events
+            .event_queue
+            .roundtrip(&mut self.state)
+            .map_err(WaylandError::Dispatch)?;
+        self.state.globals_done = true;
+        self.state
+            .request_idle_notification(&events.event_queue.handle());
+        self.connected = Instant::now();
+        self.reported = false;
+
+        events.flush()
+    }
+
+    /// Time left to the compositor to advertise an idle protocol
+    fn protocol_window(&self, now: Instant) -> Option<Duration> {
+        if self.state.is_tracking() || (self.events.is_none() && self.ever_connected) {
+            return None;
+        }
+
+        Some(PROTOCOL_WINDOW.saturating_sub(now.saturating_duration_since(self.connected)))
+    }
+
+    /// Log the advertised globals once the idle protocol is given up
+    fn report_missing_protocol(&mut self) {
+        if self.reported || !self.unavailable() {
+            return;
+        }
+        self.reported = true;
+        if self.events.is_none() {
+            eprintln!("Idle detection is unavailable, still no Wayland compositor to connect to");
+            return;
+        }
+        eprintln!(
+            "Idle detection is unavailable, {}, globals found: {}",
+            self.state.missing_protocol(),
+            self.state.globals.join(", ")
+        );
+    }
+
+    /// Drop the broken connection, a new one is attempted after the backoff delay
+    fn disconnect(&mut self, error: WaylandError) {
+        eprintln!("{error}, reconnecting in {}s", self.backoff.delay.as_secs());
+        self.events = None;
+        // keep the reported status, to resume once the notifications are recreated
+        let idle = self.state.idle;
+        self.state = Wayland::new(self.state.timeout, self.state.policy);
+        self.state.idle = idle;
+        self.backoff.failed(Instant::now());
+        self.error = Some(error.to_string());
+    }
+
+    /// Connect again once the backoff delay elapsed
+    fn reconnect(&mut self) {
+        if !self.backoff.remaining(Instant::now()).is_zero() {
+            return;
+        }
+        match Events::new() {
+            Ok(events) => {
+                let first = !self.ever_connected;
+                self.set_connection(events);
+                match self.setup() {
+                    Ok(()) => {
+                        if first {
+                            eprintln!("Connected to the Wayland compositor");
+                        } else {
+                            eprintln!("Reconnected to the Wayland compositor");
+                        }
+                        self.backoff = Backoff::new();
+                        self.error = None;
+                    }
+                    Err(e) => self.disconnect(e),
+                }
             }
+            Err(e) => self.disconnect(e),
         }
     }
 }
 
-#[allow(clippy::identity_op)]
+impl IdleSource for WaylandSource {
+    fn name(&self) -> &'static str {
+        "wayland"
+    }
+
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = self.setup() {
+            self.disconnect(e);
+        }
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>> {
+        if self.events.is_none() {
+            self.reconnect();
+        }
+        if let Some(events) = self.events.as_mut() {
+            if let Err(e) = events.dispatch(&mut self.state) {
+                self.disconnect(e);
+            }
+        }
+        self.report_missing_protocol();
+
+        Ok(std::mem::take(&mut self.state.events))
+    }
+
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        self.events.as_ref().map(|events| events.conn.as_fd())
+    }
+
+    fn next_dispatch(&self) -> Option<Duration> {
+        let now = Instant::now();
+        // wake up to give up on the idle protocol
+        let window = self.protocol_window(now).filter(|window| !window.is_zero());
+        if self.events.is_some() {
+            return window;
+        }
+
+        [Some(self.backoff.remaining(now)), window]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+
+    fn degraded(&self) -> Option<String> {
+        if let Some(error) = &self.error {
+            return Some(format!("{error}, reconnecting"));
+        }
+        self.unavailable().then(|| self.state.missing_protocol())
+    }
+
+    fn unavailable(&self) -> bool {
+        self.protocol_window(Instant::now())
+            .is_some_and(|window| window.is_zero())
+    }
+
+    fn reload(&mut self, args: &Args) -> Result<(), Box<dyn Error>> {
+        self.state.policy = args.seat_policy;
+        self.state.update_idle();
+        let timeout = idle_detection_timeout(args.idle_timeout).as_millis() as u32;
+        if timeout == self.state.timeout {
+            return Ok(());
+        }
+
+        // re-request the idle notifications with the new timeout
+        self.state.timeout = timeout;
+        let Some(events) = self.events.as_ref() else {
+            return Ok(());
+        };
+        self.state.release_notifications();
+        self.state
+            .request_idle_notification(&events.event_queue.handle());
+        events.flush()?;
+
+        Ok(())
+    }
+}
+
 impl Dispatch<wl_registry::WlRegistry, ()> for Wayland {
     fn event(
         wayland: &mut Self,
@@ -100,59 +560,95 @@ impl Dispatch<wl_registry::WlRegistry, ()> for Wayland {
         _: &Connection,
         qh: &QueueHandle<Wayland>,
     ) {
-        if let wl_registry::Event::Global {
-            name,
-            interface,
-            version,
-        } = event
-        {
-            match &interface[..] {
-                "wl_seat" => {
-                    wayland.seat =
-                        Some(registry.bind::<wl_seat::WlSeat, _, _>(name, version, qh, ()));
-                    wayland.kde_idle.as_ref().and_then(|kde_idle| {
-                        wayland
-                            .seat
-                            .as_ref()
-                            .map(|seat| kde_idle.get_idle_timeout(seat, wayland.timeout, qh, ()))
-                    });
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } => {
+                if !wayland.globals.contains(&interface) {
+                    wayland.globals.push(interface.clone());
                 }
-                "org_kde_kwin_idle" => {
-                    wayland.kde_idle =
-                        Some(registry.bind::<org_kde_kwin_idle::OrgKdeKwinIdle, _, _>(
+                match &interface[..] {
+                    "wl_seat" => {
+                        wayland.seats.push(Seat {
                             name,
-                            version,
+                            seat: registry.bind::<wl_seat::WlSeat, _, _>(
+                                name,
+                                version.min(WL_SEAT_VERSION),
+                                qh,
+                                (),
+                            ),
+                            notification: None,
+                            idle: false,
+                        });
+                    }
+                    "ext_idle_notifier_v1" => {
+                        wayland.ext_idle = Some(registry.bind::<ExtIdleNotifierV1, _, _>(
+                            name,
+                            version.min(EXT_IDLE_NOTIFIER_VERSION),
                             qh,
                             (),
                         ));
-                    wayland.seat.as_ref().and_then(|seat| {
-                        wayland.kde_idle.as_ref().map(|kde_idle| {
-                            kde_idle.get_idle_timeout(seat, wayland.timeout, qh, ())
-                        })
-                    });
+                    }
+                    "org_kde_kwin_idle" => {
+                        wayland.kde_idle =
+                            Some(registry.bind::<org_kde_kwin_idle::OrgKdeKwinIdle, _, _>(
+                                name,
+                                version.min(KDE_IDLE_VERSION),
+                                qh,
+                                (),
+                            ));
+                    }
+                    _ => return,
+                }
+                // globals announced after the initial roundtrip
+                wayland.request_idle_notification(qh);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                let Some(index) = wayland.seats.iter().position(|seat| seat.name == name) else {
+                    return;
+                };
+                if let Some(notification) = wayland.seats.remove(index).notification {
+                    notification.destroy();
                 }
-                _ => {}
+                eprintln!("Seat {name} removed");
+                wayland.update_idle();
             }
+            _ => {}
         }
     }
 }
 
-impl Dispatch<org_kde_kwin_idle_timeout::OrgKdeKwinIdleTimeout, ()> for Wayland {
+impl Dispatch<ext_idle_notification_v1::ExtIdleNotificationV1, u32> for Wayland {
+    fn event(
+        wayland: &mut Self,
+        _: &ext_idle_notification_v1::ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        seat: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_idle_notification_v1::Event::Resumed => wayland.set_seat_idle(*seat, false),
+            ext_idle_notification_v1::Event::Idled => wayland.set_seat_idle(*seat, true),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<org_kde_kwin_idle_timeout::OrgKdeKwinIdleTimeout, u32> for Wayland {
     fn event(
         wayland: &mut Self,
         _: &org_kde_kwin_idle_timeout::OrgKdeKwinIdleTimeout,
         event: org_kde_kwin_idle_timeout::Event,
-        _: &(),
+        seat: &u32,
         _: &Connection,
         _: &QueueHandle<Self>,
     ) {
         match event {
-            org_kde_kwin_idle_timeout::Event::Resumed => {
-                wayland.logic.set_resumed();
-            }
-            org_kde_kwin_idle_timeout::Event::Idle => {
-                wayland.logic.set_idle();
-            }
+            org_kde_kwin_idle_timeout::Event::Resumed => wayland.set_seat_idle(*seat, false),
+            org_kde_kwin_idle_timeout::Event::Idle => wayland.set_seat_idle(*seat, true),
             _ => {}
         }
     }
@@ -170,6 +666,18 @@ impl Dispatch<wl_seat::WlSeat, ()> for Wayland {
     }
 }
 
+impl Dispatch<ext_idle_notifier_v1::ExtIdleNotifierV1, ()> for Wayland {
+    fn event(
+        _: &mut Self,
+        _: &ext_idle_notifier_v1::ExtIdleNotifierV1,
+        _: ext_idle_notifier_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
 impl Dispatch<org_kde_kwin_idle::OrgKdeKwinIdle, ()> for Wayland {
     fn event(
         _: &mut Self,
@@ -181,3 +689,236 @@ impl Dispatch<org_kde_kwin_idle::OrgKdeKwinIdle, ()> for Wayland {
     ) {
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+    use wayland_protocols::ext::idle_notify::v1::server::{
+        ext_idle_notification_v1::{self as server_notification, ExtIdleNotificationV1},
+        ext_idle_notifier_v1::{self as server_notifier, ExtIdleNotifierV1 as ServerNotifier},
+    };
+    use wayland_server::protocol::wl_seat::{self as server_seat, WlSeat as ServerSeat};
+    use wayland_server::{
+        backend::ClientData, Client, DataInit, Display, DisplayHandle, GlobalDispatch,
+        ListeningSocket, New,
+    };
+
+    /// Compositor advertising a seat and `ext_idle_notifier_v1`
+    #[derive(Default)]
+    struct Compositor {
+        notifications: Vec<ExtIdleNotificationV1>,
+    }
+
+    struct TestClient;
+
+    impl ClientData for TestClient {}
+
+    enum Command {
+        Idle,
+        Restart,
+    }
+
+    fn compositor() -> Display<Compositor> {
+        let display = Display::new().unwrap();
+        let handle = display.handle();
+        handle.create_global::<Compositor, ServerSeat, ()>(1, ());
+        handle.create_global::<Compositor, ServerNotifier, ()>(1, ());
+        display
+    }
+
+    /// Serve the compositor on `socket` until the commands are dropped, a restart
+    /// closes the connections of the clients
+    fn serve(socket: ListeningSocket, commands: mpsc::Receiver<Command>) {
+        let mut display = compositor();
+        let mut state = Compositor::default();
+        // the user goes idle once the client requested its notifications
+        let mut idle = false;
+        loop {
+            match commands.try_recv() {
+                Ok(Command::Idle) => idle = true,
+                Ok(Command::Restart) => {
+                    display = compositor();
+                    state.notifications.clear();
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+            if let Some(stream) = socket.accept().unwrap() {
+                display
+                    .handle()
+                    .insert_client(stream, Arc::new(TestClient))
+                    .unwrap();
+            }
+            display.dispatch_clients(&mut state).unwrap();
+            if idle && !state.notifications.is_empty() {
+                state.notifications.iter().for_each(|n| n.idled());
+                idle = false;
+            }
+            display.flush_clients().unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    impl GlobalDispatch<ServerSeat, ()> for Compositor {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<ServerSeat>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            data_init.init(resource, ());
+        }
+    }
+
+    impl wayland_server::Dispatch<ServerSeat, ()> for Compositor {
+        fn request(
+            _: &mut Self,
+            _: &Client,
+            _: &ServerSeat,
+            _: server_seat::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+        }
+    }
+
+    impl GlobalDispatch<ServerNotifier, ()> for Compositor {
+        fn bind(
+            _: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<ServerNotifier>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            data_init.init(resource, ());
+        }
+    }
+
+    impl wayland_server::Dispatch<ServerNotifier, ()> for Compositor {
+        fn request(
+            state: &mut Self,
+            _: &Client,
+            _: &ServerNotifier,
+            request: server_notifier::Request,
+            _: &(),
+            _: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            if let server_notifier::Request::GetIdleNotification { id, .. } = request {
+                state.notifications.push(data_init.init(id, ()));
+            }
+        }
+    }
+
+    impl wayland_server::Dispatch<ExtIdleNotificationV1, ()> for Compositor {
+        fn request(
+            _: &mut Self,
+            _: &Client,
+            _: &ExtIdleNotificationV1,
+            _: server_notification::Request,
+            _: &(),
+            _: &DisplayHandle,
+            _: &mut DataInit<'_, Self>,
+        ) {
+        }
+    }
+
+    /// Dispatch the source until it reports some events or `done`
+    fn wait_events(
+        source: &mut WaylandSource,
+        done: impl Fn(&WaylandSource) -> bool,
+    ) -> Vec<IdleEvent> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            let events = source.dispatch().unwrap();
+            if !events.is_empty() || done(source) {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("no wayland event");
+    }
+
+    #[test]
+    fn test_backoff() {
+        let now = Instant::now();
+        let mut backoff = Backoff::new();
+        assert_eq!(backoff.remaining(now), Duration::ZERO);
+
+        backoff.failed(now);
+        assert_eq!(backoff.remaining(now), Duration::from_secs(1));
+        backoff.failed(now);
+        assert_eq!(backoff.remaining(now), Duration::from_secs(2));
+        assert_eq!(
+            backoff.remaining(now + Duration::from_secs(3)),
+            Duration::ZERO
+        );
+
+        for _ in 0..10 {
+            backoff.failed(now);
+        }
+        assert_eq!(backoff.remaining(now), MAX_RECONNECT_DELAY);
+    }
+
+    #[test]
+    fn test_seat_policy() {
+        let any = SeatPolicy::AnyActive;
+        assert!(!any.is_idle([false, true].into_iter()));
+        assert!(any.is_idle([true, true].into_iter()));
+
+        let all = SeatPolicy::AllActive;
+        assert!(all.is_idle([false, true].into_iter()));
+        assert!(!all.is_idle([false, false].into_iter()));
+    }
+
+    #[test]
+    fn test_missing_protocol() {
+        let wayland = Wayland::new(60_000, SeatPolicy::AnyActive);
+        assert_eq!(
+            wayland.missing_protocol(),
+            "the compositor advertises neither ext_idle_notifier_v1 nor org_kde_kwin_idle"
+        );
+    }
+
+    #[test]
+    fn test_reconnect_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wayland-test");
+        let socket = ListeningSocket::bind_absolute(path.clone()).unwrap();
+        std::env::set_var("WAYLAND_DISPLAY", &path);
+        let (commands, receiver) = mpsc::channel();
+        let server = thread::spawn(move || serve(socket, receiver));
+
+        let mut args = Args::default();
+        let mut source = WaylandSource::new(&args);
+        source.init().unwrap();
+        assert!(source.state.is_tracking());
+        commands.send(Command::Idle).unwrap();
+        assert_eq!(wait_events(&mut source, |_| false), [IdleEvent::Idle]);
+
+        // the notifications of a new connection start active
+        commands.send(Command::Restart).unwrap();
+        let events = wait_events(&mut source, |source| source.events.is_none());
+        assert!(events.is_empty());
+        assert!(source.degraded().is_some());
+        assert_eq!(wait_events(&mut source, |_| false), [IdleEvent::Resumed]);
+        assert!(source.degraded().is_none());
+
+        // so do the notifications requested again with a new timeout
+        commands.send(Command::Idle).unwrap();
+        assert_eq!(wait_events(&mut source, |_| false), [IdleEvent::Idle]);
+        args.idle_timeout = 30;
+        source.reload(&args).unwrap();
+        assert_eq!(source.dispatch().unwrap(), [IdleEvent::Resumed]);
+
+        drop(commands);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::time::Duration;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::screensaver::{self, ConnectionExt};
+use x11rb::protocol::xproto::Window;
+use x11rb::rust_connection::RustConnection;
+
+use crate::helpers::idle_detection_timeout;
+use crate::source::{IdleEvent, IdleSource};
+use crate::Args;
+
+/// Time between two queries while idle, to notice the user coming back
+const RESUME_POLL: Duration = Duration::from_secs(1);
+
+/// X11 idle source, polling the MIT-SCREEN-SAVER extension
+#[derive(Debug)]
+pub struct X11Source {
+    /// X server connection
+    conn: RustConnection,
+    /// Root window of the default screen
+    root: Window,
+    /// Time without input before being idle
+    timeout: Duration,
+    /// Last reported state
+    idle: bool,
+    /// Time since the last input, at the last query
+    idle_time: Duration,
+}
+
+impl X11Source {
+    pub fn new(args: &Args) -> Result<X11Source, Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        if conn
+            .extension_information(screensaver::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err("The X server doesn't support the MIT-SCREEN-SAVER extension".into());
+        }
+        let root = conn.setup().roots[screen_num].root;
+
+        Ok(X11Source {
+            conn,
+            root,
+            timeout: idle_detection_timeout(args.idle_timeout),
+            idle: false,
+            idle_time: Duration::ZERO,
+        })
+    }
+
+    /// Time since the last user input
+    pub fn idle_time(&self) -> Result<Duration, Box<dyn Error>> {
+        let info = self.conn.screensaver_query_info(self.root)?.reply()?;
+        Ok(Duration::from_millis(info.ms_since_user_input.into()))
+    }
+}
+
+/// Event to emit for the given idle time, if the state changed
+fn transition(idle: bool, idle_time: Duration, timeout: Duration) -> Option<IdleEvent> {
+    match (idle, idle_time >= timeout) {
+        (false, true) => Some(IdleEvent::Idle),
+        (true, false) => Some(IdleEvent::Resumed),
+        _ => None,
+    }
+}
+
+/// Time before the next query, the earliest the user can go idle, or soon to see them come back
+fn next_query(idle: bool, idle_time: Duration, timeout: Duration) -> Duration {
+    if idle {
+        RESUME_POLL
+    } else {
+        timeout.saturating_sub(idle_time).max(RESUME_POLL)
+    }
+}
+
+impl IdleSource for X11Source {
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<IdleEvent>, Box<dyn Error>> {
+        let idle_time = self.idle_time()?;
+        self.idle_time = idle_time;
+        Ok(transition(self.idle, idle_time, self.timeout)
+            .map(|event| {
+                self.idle = event == IdleEvent::Idle;
+                vec![event]
+            })
+            .unwrap_or_default())
+    }
+
+    fn next_dispatch(&self) -> Option<Duration> {
+        Some(next_query(self.idle, self.idle_time, self.timeout))
+    }
+
+    fn reload(&mut self, args: &Args) -> Result<(), Box<dyn Error>> {
+        self.timeout = idle_detection_timeout(args.idle_timeout);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_around_the_timeout() {
+        let timeout = Duration::from_secs(60);
+
+        assert_eq!(transition(false, Duration::from_secs(59), timeout), None);
+        assert_eq!(
+            transition(false, Duration::from_secs(60), timeout),
+            Some(IdleEvent::Idle)
+        );
+        assert_eq!(transition(true, Duration::from_secs(600), timeout), None);
+        assert_eq!(
+            transition(true, Duration::from_millis(10), timeout),
+            Some(IdleEvent::Resumed)
+        );
+
+        assert_eq!(
+            next_query(false, Duration::from_secs(20), timeout),
+            Duration::from_secs(40)
+        );
+        assert_eq!(next_query(false, timeout, timeout), RESUME_POLL);
+        assert_eq!(
+            next_query(true, Duration::from_secs(600), timeout),
+            RESUME_POLL
+        );
+    }
+
+    /// Run with `xvfb-run cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_query_idle_time_on_the_display() {
+        let args = Args {
+            idle_timeout: 60,
+            ..Default::default()
+        };
+        let mut source = X11Source::new(&args).expect("Can't connect to $DISPLAY");
+
+        assert!(source.idle_time().is_ok());
+        assert!(source.dispatch().is_ok());
+    }
+}